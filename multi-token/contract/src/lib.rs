@@ -0,0 +1,352 @@
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{
+    collections::btree_map::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use borsh::{io::Error, BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use sdk::RunResult;
+
+impl sdk::HyleContract for MultiToken {
+    /// Entry point of the contract's logic
+    fn execute(&mut self, contract_input: &sdk::ContractInput) -> RunResult {
+        // Parse contract inputs
+        let (action, ctx) =
+            sdk::utils::parse_raw_contract_input::<MultiTokenAction>(contract_input)?;
+
+        // Execute the given action, errors are turned into the sdk's error string only here
+        let events = self
+            .execute_action(&ctx.caller.0, action)
+            .map_err(|e| e.to_string())?;
+
+        Ok((MultiTokenOutput::V1(events).encode(), ctx, alloc::vec![]))
+    }
+
+    /// In this example, we serialize the full state on-chain.
+    fn commit(&self) -> sdk::StateCommitment {
+        sdk::StateCommitment(self.as_bytes().expect("Failed to encode MultiToken"))
+    }
+}
+
+pub type TokenId = u64;
+
+/// The state of the contract, that is totally serialized on-chain.
+/// A single contract holds any number of tokens, each with its own balances.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MultiToken {
+    pub tokens: BTreeMap<TokenId, Token>,
+    pub next_token_id: TokenId, // Id given to the next created token
+}
+
+/// A token of the contract, and the balances of its holders
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Token {
+    pub name: String,
+    pub creator: String,
+    pub total_supply: u128,
+    pub balances: BTreeMap<String, u128>, // Balances for each account
+}
+
+/// A transfer of one token, as part of a batch
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct TokenTransfer {
+    pub token_id: TokenId,
+    pub recipient: String,
+    pub amount: u128,
+}
+
+/// Enum representing possible calls to the contract functions.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum MultiTokenAction {
+    /// Creates a new token, whose whole supply goes to the caller
+    CreateToken {
+        name: String,
+        supply: u128,
+    },
+    Transfer {
+        token_id: TokenId,
+        recipient: String,
+        amount: u128,
+    },
+    BatchTransfer {
+        transfers: Vec<TokenTransfer>,
+    },
+}
+
+/// Enum representing the events emitted by the contract functions.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum MultiTokenEvent {
+    TokenCreated {
+        token_id: TokenId,
+        name: String,
+        creator: String,
+        supply: u128,
+    },
+    Transferred {
+        token_id: TokenId,
+        sender: String,
+        recipient: String,
+        amount: u128,
+    },
+}
+
+/// The program output of the contract: the events emitted by the executed action.
+/// It is versioned so that indexers can keep decoding the outputs of older versions of the contract.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum MultiTokenOutput {
+    V1(Vec<MultiTokenEvent>),
+}
+
+/// Enum representing the errors returned by the contract functions.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum MultiTokenError {
+    TokenNotFound(TokenId),
+    AccountNotFound {
+        token_id: TokenId,
+        account: String,
+    },
+    InsufficientBalance {
+        token_id: TokenId,
+        account: String,
+        balance: u128,
+        amount: u128,
+    },
+    Overflow,
+}
+
+impl fmt::Display for MultiTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiTokenError::TokenNotFound(token_id) => write!(f, "Token {token_id} not found"),
+            MultiTokenError::AccountNotFound { token_id, account } => {
+                write!(f, "Account {account} not found for token {token_id}")
+            }
+            MultiTokenError::InsufficientBalance {
+                token_id,
+                account,
+                balance,
+                amount,
+            } => write!(
+                f,
+                "Insufficient balance of token {token_id}: {account} has {balance} but needs {amount}"
+            ),
+            MultiTokenError::Overflow => write!(f, "Arithmetic overflow"),
+        }
+    }
+}
+
+impl fmt::Display for MultiTokenEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiTokenEvent::TokenCreated {
+                token_id,
+                name,
+                creator,
+                supply,
+            } => write!(
+                f,
+                "Created token {token_id} ({name}) with supply {supply} for {creator}"
+            ),
+            MultiTokenEvent::Transferred {
+                token_id,
+                sender,
+                recipient,
+                amount,
+            } => write!(
+                f,
+                "Transferred {amount} of token {token_id} from {sender} to {recipient}"
+            ),
+        }
+    }
+}
+
+impl MultiTokenOutput {
+    /// Encodes the output as the string returned by `execute`: the sdk expects a string,
+    /// so the borsh encoding is hex-encoded.
+    pub fn encode(&self) -> String {
+        hex::encode(borsh::to_vec(self).expect("Failed to encode MultiTokenOutput"))
+    }
+
+    pub fn decode(output: &str) -> Result<Self, String> {
+        let bytes = hex::decode(output).map_err(|e| e.to_string())?;
+        borsh::from_slice(&bytes).map_err(|e| e.to_string())
+    }
+
+    pub fn events(&self) -> &[MultiTokenEvent] {
+        match self {
+            MultiTokenOutput::V1(events) => events,
+        }
+    }
+}
+
+impl MultiToken {
+    pub fn as_bytes(&self) -> Result<Vec<u8>, Error> {
+        borsh::to_vec(self)
+    }
+
+    /// Applies `action` sent by `caller`.
+    pub fn execute_action(
+        &mut self,
+        caller: &str,
+        action: MultiTokenAction,
+    ) -> Result<Vec<MultiTokenEvent>, MultiTokenError> {
+        match action {
+            MultiTokenAction::CreateToken { name, supply } => {
+                self.create_token(caller, name, supply)
+            }
+            MultiTokenAction::Transfer {
+                token_id,
+                recipient,
+                amount,
+            } => self.transfer(token_id, caller, &recipient, amount),
+            MultiTokenAction::BatchTransfer { transfers } => self.batch_transfer(caller, transfers),
+        }
+    }
+
+    pub fn token(&self, token_id: TokenId) -> Result<&Token, MultiTokenError> {
+        self.tokens
+            .get(&token_id)
+            .ok_or(MultiTokenError::TokenNotFound(token_id))
+    }
+
+    pub fn balance_of(&self, token_id: TokenId, account: &str) -> Result<u128, MultiTokenError> {
+        self.token(token_id)?
+            .balances
+            .get(account)
+            .copied()
+            .ok_or_else(|| MultiTokenError::AccountNotFound {
+                token_id,
+                account: account.to_string(),
+            })
+    }
+
+    /// Creates a token with the next available id, and credits its supply to `creator`.
+    pub fn create_token(
+        &mut self,
+        creator: &str,
+        name: String,
+        supply: u128,
+    ) -> Result<Vec<MultiTokenEvent>, MultiTokenError> {
+        let token_id = self.next_token_id;
+        self.next_token_id = token_id.checked_add(1).ok_or(MultiTokenError::Overflow)?;
+
+        let mut balances = BTreeMap::new();
+        balances.insert(creator.to_string(), supply);
+        self.tokens.insert(
+            token_id,
+            Token {
+                name: name.clone(),
+                creator: creator.to_string(),
+                total_supply: supply,
+                balances,
+            },
+        );
+
+        Ok(vec![MultiTokenEvent::TokenCreated {
+            token_id,
+            name,
+            creator: creator.to_string(),
+            supply,
+        }])
+    }
+
+    pub fn transfer(
+        &mut self,
+        token_id: TokenId,
+        sender: &str,
+        recipient: &str,
+        amount: u128,
+    ) -> Result<Vec<MultiTokenEvent>, MultiTokenError> {
+        self.debit(token_id, sender, amount)?;
+        self.credit(token_id, recipient, amount)?;
+
+        Ok(vec![MultiTokenEvent::Transferred {
+            token_id,
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            amount,
+        }])
+    }
+
+    /// Transfers several tokens from `sender` at once. Either all transfers are applied, or none.
+    pub fn batch_transfer(
+        &mut self,
+        sender: &str,
+        transfers: Vec<TokenTransfer>,
+    ) -> Result<Vec<MultiTokenEvent>, MultiTokenError> {
+        // Transfers are applied to a copy, which replaces the state only if all of them succeed
+        let mut state = self.clone();
+        let mut events = Vec::with_capacity(transfers.len());
+        for transfer in &transfers {
+            events.extend(state.transfer(
+                transfer.token_id,
+                sender,
+                &transfer.recipient,
+                transfer.amount,
+            )?);
+        }
+        *self = state;
+
+        Ok(events)
+    }
+
+    fn debit(
+        &mut self,
+        token_id: TokenId,
+        account: &str,
+        amount: u128,
+    ) -> Result<(), MultiTokenError> {
+        let balance = self.balance_of(token_id, account)?;
+        if balance < amount {
+            return Err(MultiTokenError::InsufficientBalance {
+                token_id,
+                account: account.to_string(),
+                balance,
+                amount,
+            });
+        }
+        self.token_mut(token_id)?
+            .balances
+            .insert(account.to_string(), balance - amount);
+        Ok(())
+    }
+
+    fn credit(
+        &mut self,
+        token_id: TokenId,
+        account: &str,
+        amount: u128,
+    ) -> Result<(), MultiTokenError> {
+        let balance = self
+            .token_mut(token_id)?
+            .balances
+            .entry(account.to_string())
+            .or_insert(0);
+        *balance = balance
+            .checked_add(amount)
+            .ok_or(MultiTokenError::Overflow)?;
+        Ok(())
+    }
+
+    fn token_mut(&mut self, token_id: TokenId) -> Result<&mut Token, MultiTokenError> {
+        self.tokens
+            .get_mut(&token_id)
+            .ok_or(MultiTokenError::TokenNotFound(token_id))
+    }
+}
+
+impl From<sdk::StateCommitment> for MultiToken {
+    fn from(state: sdk::StateCommitment) -> Self {
+        borsh::from_slice(&state.0)
+            .map_err(|_| "Could not decode multi-token state".to_string())
+            .unwrap()
+    }
+}
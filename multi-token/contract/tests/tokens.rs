@@ -0,0 +1,118 @@
+use contract::{
+    MultiToken, MultiTokenAction, MultiTokenError, MultiTokenEvent, MultiTokenOutput, TokenTransfer,
+};
+use sdk::{Blob, BlobData, BlobIndex, ContractInput, HyleContract, TxHash};
+
+const ALICE: &str = "alice.multi_token";
+const BOB: &str = "bob.multi_token";
+
+fn execute(
+    state: &mut MultiToken,
+    identity: &str,
+    action: MultiTokenAction,
+) -> Result<Vec<MultiTokenEvent>, String> {
+    let input = ContractInput {
+        state: state.as_bytes().unwrap(),
+        identity: identity.into(),
+        index: BlobIndex(0),
+        blobs: vec![Blob {
+            contract_name: "multi_token".into(),
+            data: BlobData(borsh::to_vec(&action).unwrap()),
+        }],
+        tx_hash: TxHash("01".to_string()),
+        tx_ctx: None,
+        private_input: vec![],
+    };
+    let (output, _, _) = state.execute(&input)?;
+    Ok(MultiTokenOutput::decode(&output)?.events().to_vec())
+}
+
+/// State where alice created token 0, `GLD` with 100 units, and token 1, `SLV` with 50 units.
+fn state() -> MultiToken {
+    let mut state = MultiToken::default();
+    for (name, supply) in [("GLD", 100), ("SLV", 50)] {
+        let action = MultiTokenAction::CreateToken {
+            name: name.to_string(),
+            supply,
+        };
+        execute(&mut state, ALICE, action).unwrap();
+    }
+    state
+}
+
+fn transfer(token_id: u64, amount: u128) -> TokenTransfer {
+    TokenTransfer {
+        token_id,
+        recipient: BOB.to_string(),
+        amount,
+    }
+}
+
+#[test]
+fn tokens_get_distinct_ids_and_balances() {
+    let mut state = state();
+    assert_eq!(state.next_token_id, 2);
+    assert_eq!(state.token(1).unwrap().name, "SLV");
+
+    let action = MultiTokenAction::Transfer {
+        token_id: 0,
+        recipient: BOB.to_string(),
+        amount: 30,
+    };
+    let events = execute(&mut state, ALICE, action).unwrap();
+    assert_eq!(
+        events,
+        vec![MultiTokenEvent::Transferred {
+            token_id: 0,
+            sender: ALICE.to_string(),
+            recipient: BOB.to_string(),
+            amount: 30,
+        }]
+    );
+    assert_eq!(state.balance_of(0, ALICE), Ok(70));
+    assert_eq!(state.balance_of(0, BOB), Ok(30));
+    // Balances of the other token are untouched
+    assert_eq!(state.balance_of(1, ALICE), Ok(50));
+    assert_eq!(
+        state.balance_of(1, BOB),
+        Err(MultiTokenError::AccountNotFound {
+            token_id: 1,
+            account: BOB.to_string(),
+        })
+    );
+}
+
+#[test]
+fn transfer_of_an_unknown_token_fails() {
+    let mut state = state();
+    let err = state.transfer(2, ALICE, BOB, 1).unwrap_err();
+    assert_eq!(err, MultiTokenError::TokenNotFound(2));
+}
+
+#[test]
+fn batch_transfer_is_all_or_nothing() {
+    let mut state = state();
+
+    let events = state
+        .batch_transfer(ALICE, vec![transfer(0, 10), transfer(1, 20)])
+        .unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(state.balance_of(0, BOB), Ok(10));
+    assert_eq!(state.balance_of(1, BOB), Ok(20));
+
+    // The last transfer exceeds the balance, so the first ones are not applied either
+    let before = state.clone();
+    let err = state
+        .batch_transfer(ALICE, vec![transfer(0, 10), transfer(1, 31)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        MultiTokenError::InsufficientBalance {
+            token_id: 1,
+            account: ALICE.to_string(),
+            balance: 30,
+            amount: 31,
+        }
+    );
+    assert_eq!(state.tokens, before.tokens);
+}
@@ -0,0 +1,272 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use client_sdk::helpers::risc0::Risc0Prover;
+use client_sdk::rest_client::NodeApiHttpClient;
+use contract::MultiToken;
+use contract::MultiTokenAction;
+use contract::MultiTokenError;
+use contract::MultiTokenOutput;
+use contract::TokenId;
+use contract::TokenTransfer;
+use sdk::api::APIRegisterContract;
+use sdk::BlobTransaction;
+use sdk::ProofTransaction;
+use sdk::{ContractInput, HyleContract};
+
+// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
+// The ELF is used for proving and the ID is used for verification.
+use methods::{GUEST_ELF, GUEST_ID};
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[clap(long, short)]
+    reproducible: bool,
+
+    #[arg(long, default_value = "http://localhost:4321")]
+    pub host: String,
+
+    #[arg(long, default_value = "multi_token")]
+    pub contract_name: String,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Registers the contract, without any token
+    Register {},
+    /// Creates a new token, whose whole supply goes to `creator`
+    CreateToken {
+        creator: String,
+        name: String,
+        supply: u128,
+    },
+    Transfer {
+        token_id: TokenId,
+        from: String,
+        to: String,
+        amount: u128,
+    },
+    /// Transfers from one account to all the recipients listed in a CSV file
+    BatchTransfer {
+        from: String,
+        /// CSV file with one `token_id,recipient,amount` line per transfer
+        file: PathBuf,
+    },
+    Balance {
+        token_id: TokenId,
+        of: String,
+    },
+    /// Lists the tokens of the contract
+    Tokens {},
+}
+
+#[tokio::main]
+async fn main() {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+
+    let client = NodeApiHttpClient::new(cli.host).unwrap();
+
+    let contract_name = &cli.contract_name;
+
+    let prover = Risc0Prover::new(GUEST_ELF);
+
+    match cli.command {
+        Commands::Register {} => {
+            // Build initial state of contract
+            let initial_state = MultiToken::default();
+            println!("Initial state: {:?}", initial_state);
+
+            // Send the transaction to register the contract
+            let res = client
+                .register_contract(&APIRegisterContract {
+                    verifier: "risc0-1".into(),
+                    program_id: sdk::ProgramId(sdk::to_u8_array(&GUEST_ID).to_vec()),
+                    state_commitment: initial_state.commit(),
+                    contract_name: contract_name.clone().into(),
+                })
+                .await
+                .unwrap();
+            println!("✅ Register contract tx sent. Tx hash: {}", res);
+        }
+        Commands::CreateToken {
+            creator,
+            name,
+            supply,
+        } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = MultiTokenAction::CreateToken { name, supply };
+            send_action(&client, &prover, contract_name, state, creator, action).await;
+        }
+        Commands::Transfer {
+            token_id,
+            from,
+            to,
+            amount,
+        } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = MultiTokenAction::Transfer {
+                token_id,
+                recipient: to,
+                amount,
+            };
+            send_action(&client, &prover, contract_name, state, from, action).await;
+        }
+        Commands::BatchTransfer { from, file } => {
+            let state = fetch_state(&client, contract_name).await;
+            let transfers = read_transfers(&file);
+            println!("Sending {} transfers from {}", transfers.len(), from);
+            let action = MultiTokenAction::BatchTransfer { transfers };
+            send_action(&client, &prover, contract_name, state, from, action).await;
+        }
+        Commands::Balance { token_id, of } => {
+            // Fetch the state from the node
+            let state = fetch_state(&client, contract_name).await;
+
+            let token = state.token(token_id).unwrap_or_else(|err| exit_with(err));
+            let balance = state
+                .balance_of(token_id, &of)
+                .unwrap_or_else(|err| exit_with(err));
+            println!(
+                "Balance of {} for token {} ({}): {}",
+                of, token_id, token.name, balance
+            );
+        }
+        Commands::Tokens {} => {
+            let state = fetch_state(&client, contract_name).await;
+            for (token_id, token) in &state.tokens {
+                println!(
+                    "Token {}: {}, created by {}, total supply {}, {} holders",
+                    token_id,
+                    token.name,
+                    token.creator,
+                    token.total_supply,
+                    token.balances.len()
+                );
+            }
+        }
+    }
+}
+
+/// Fetches the current state of the contract from the node.
+async fn fetch_state(client: &NodeApiHttpClient, contract_name: &str) -> MultiToken {
+    client
+        .get_contract(&contract_name.into())
+        .await
+        .unwrap()
+        .state
+        .into()
+}
+
+/// Sends a blob transaction for `action` on behalf of `identity`, then proves and sends its proof.
+/// `initial_state` is the current state of the contract, as fetched from the node.
+async fn send_action(
+    client: &NodeApiHttpClient,
+    prover: &Risc0Prover<'_>,
+    contract_name: &str,
+    mut initial_state: MultiToken,
+    identity: String,
+    action: MultiTokenAction,
+) {
+    // Run the action locally first, so that failures are reported before sending anything
+    if let Err(err) = initial_state
+        .clone()
+        .execute_action(&identity, action.clone())
+    {
+        exit_with(err);
+    }
+
+    // ----
+    // Build the blob transaction
+    // ----
+
+    let blobs = vec![sdk::Blob {
+        contract_name: contract_name.into(),
+        data: sdk::BlobData(borsh::to_vec(&action).expect("failed to encode BlobData")),
+    }];
+    let blob_tx = BlobTransaction::new(identity.clone(), blobs.clone());
+
+    // Send the blob transaction
+    let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
+    println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
+
+    // ----
+    // Prove the state transition
+    // ----
+
+    // Build the contract input
+    let inputs = ContractInput {
+        state: initial_state.as_bytes().unwrap(),
+        identity: identity.into(),
+        tx_hash: blob_tx_hash,
+        private_input: vec![],
+        tx_ctx: None,
+        blobs: blobs.clone(),
+        index: sdk::BlobIndex(0),
+    };
+
+    let res = initial_state.execute(&inputs).unwrap();
+    let output = MultiTokenOutput::decode(&res.0).expect("failed to decode program output");
+    for event in output.events() {
+        println!("🚀 Executed: {}", event);
+    }
+
+    // Generate the zk proof
+    let proof = prover.prove(inputs).await.unwrap();
+
+    let proof_tx = ProofTransaction {
+        proof,
+        contract_name: contract_name.into(),
+    };
+
+    // Send the proof transaction
+    let proof_tx_hash = client.send_tx_proof(&proof_tx).await.unwrap();
+    println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+}
+
+/// Reads `token_id,recipient,amount` lines from a CSV file, skipping empty lines and `#` comments.
+fn read_transfers(file: &Path) -> Vec<TokenTransfer> {
+    let content = std::fs::read_to_string(file).expect("failed to read transfers file");
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [token_id, recipient, amount] = fields[..] else {
+                panic!("line {line_number}: expected `token_id,recipient,amount`");
+            };
+            TokenTransfer {
+                token_id: token_id
+                    .parse()
+                    .unwrap_or_else(|err| panic!("line {line_number}: {err}")),
+                recipient: recipient.to_string(),
+                amount: amount
+                    .parse()
+                    .unwrap_or_else(|err| panic!("line {line_number}: {err}")),
+            }
+        })
+        .collect()
+}
+
+/// Prints `err` and exits with a code specific to its kind, so that scripts can match on it.
+fn exit_with(err: MultiTokenError) -> ! {
+    let code = match err {
+        MultiTokenError::TokenNotFound(_) => 2,
+        MultiTokenError::AccountNotFound { .. } => 3,
+        MultiTokenError::InsufficientBalance { .. } => 4,
+        MultiTokenError::Overflow => 5,
+    };
+    eprintln!("❌ {}", err);
+    std::process::exit(code);
+}
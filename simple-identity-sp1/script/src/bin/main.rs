@@ -1,11 +1,19 @@
 use clap::{Parser, Subcommand};
 use client_sdk::helpers::sp1::SP1Prover;
-use contract::{IdentityAction, IdentityContractState};
+use client_sdk::rest_client::NodeApiHttpClient;
+use contract::attributes::{Attestation, AttributeOpening, AttributeQuery, Predicate};
+use contract::keys::{PublicKey, SecretKey};
+use contract::{
+    AttributeProof, Authorization, ExpiryParams, IdentityAction, IdentityContractState,
+    IdentityOutput, KdfParams, MultisigSignatures, PasswordChange, Registration, Secret,
+    DEFAULT_ITERATIONS, SALT_LEN,
+};
 use sdk::api::APIRegisterContract;
 use sdk::BlobTransaction;
 use sdk::ContractInput;
 use sdk::HyleContract;
 use sdk::ProofTransaction;
+use sdk::TxHash;
 
 use sp1_sdk::include_elf;
 
@@ -28,7 +36,18 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    RegisterContract {},
+    RegisterContract {
+        /// Iterations of PBKDF2 hashing the passwords
+        #[arg(long, default_value_t = DEFAULT_ITERATIONS)]
+        iterations: u32,
+        /// Number of blocks accounts are valid for, after their registration or renewal.
+        /// Accounts never expire if not set.
+        #[arg(long)]
+        expiry: Option<u64>,
+        /// Number of blocks after its expiry before an account can be registered by someone else
+        #[arg(long, default_value = "1000")]
+        grace_period: u64,
+    },
     RegisterIdentity {
         identity: String,
         password: String,
@@ -38,6 +57,161 @@ enum Commands {
         password: String,
         nonce: u32,
     },
+    /// Replaces the password of an identity, proving the knowledge of the old one
+    ChangePassword {
+        identity: String,
+        old_password: String,
+        new_password: String,
+        nonce: u32,
+        /// Iterations of PBKDF2 hashing the new password, at least those of the contract
+        #[arg(long)]
+        iterations: Option<u32>,
+    },
+    /// Registers an identity verified with signatures instead of a password
+    RegisterKey {
+        identity: String,
+        /// `ed25519:<hex>`, `secp256k1:<hex>` or `ethereum:<hex>`
+        secret_key: SecretKey,
+    },
+    /// Verifies an identity registered with `register-key`, signing the authorization of the transaction
+    VerifyKey {
+        identity: String,
+        secret_key: SecretKey,
+        nonce: u32,
+    },
+    /// Sets the guardians able to recover an identity, `threshold` of them being needed
+    SetGuardians {
+        identity: String,
+        /// Password, or secret key as `<scheme>:<hex>`
+        secret: Secret,
+        nonce: u32,
+        threshold: u32,
+        guardians: Vec<String>,
+    },
+    /// Prints the digest of a new credential, to be approved by the guardians of an identity
+    RecoveryCredential {
+        /// New password, or new secret key as `<scheme>:<hex>`
+        secret: Secret,
+        /// Salt of a new password, drawn at random if not given
+        #[arg(long)]
+        salt: Option<String>,
+    },
+    /// Approves a new credential for an identity, as one of its guardians
+    ApproveRecovery {
+        identity: String,
+        guardian: String,
+        guardian_secret: Secret,
+        /// Nonce of the guardian
+        nonce: u32,
+        /// Digest printed by `recovery-credential`
+        credential: String,
+    },
+    /// Gives an identity the new credential approved by its guardians
+    CompleteRecovery {
+        identity: String,
+        secret: Secret,
+        /// Salt printed by `recovery-credential`, for a new password
+        #[arg(long)]
+        salt: Option<String>,
+    },
+    /// Delegates the verifications of an identity to a session key, on the given contracts only
+    CreateSession {
+        identity: String,
+        secret: Secret,
+        nonce: u32,
+        /// Secret key of the session, as `<scheme>:<hex>`
+        session_key: SecretKey,
+        contracts: Vec<String>,
+        /// Number of blocks the session is valid for
+        #[arg(long, default_value = "100")]
+        blocks: u64,
+    },
+    /// Revokes a session of an identity
+    RevokeSession {
+        identity: String,
+        secret: Secret,
+        nonce: u32,
+        /// Public key of the session, as printed by `create-session`
+        session: PublicKey,
+    },
+    /// Verifies an identity with a session key, signing the authorization of the transaction
+    VerifySession {
+        identity: String,
+        session_key: SecretKey,
+        nonce: u32,
+    },
+    /// Registers an identity held by `threshold` of the `members` public keys
+    RegisterMultisig {
+        identity: String,
+        threshold: u32,
+        members: Vec<PublicKey>,
+        /// Secret key of a member signing the registration, repeated for each signer
+        #[arg(long = "signer")]
+        signers: Vec<SecretKey>,
+    },
+    /// Verifies a multisig identity, signing the authorization of the transaction with each signer
+    VerifyMultisig {
+        identity: String,
+        nonce: u32,
+        #[arg(long = "signer")]
+        signers: Vec<SecretKey>,
+    },
+    /// Adds a member to a multisig identity
+    AddMember {
+        identity: String,
+        nonce: u32,
+        member: PublicKey,
+        #[arg(long = "signer")]
+        signers: Vec<SecretKey>,
+    },
+    /// Removes a member from a multisig identity
+    RemoveMember {
+        identity: String,
+        nonce: u32,
+        member: PublicKey,
+        #[arg(long = "signer")]
+        signers: Vec<SecretKey>,
+    },
+    /// Deletes an identity, whose name can then be registered again
+    DeleteIdentity {
+        identity: String,
+        secret: Secret,
+        nonce: u32,
+    },
+    /// Extends the expiry of an identity
+    RenewIdentity {
+        identity: String,
+        secret: Secret,
+        nonce: u32,
+    },
+    /// Attests an attribute of an identity as an issuer, and prints the attestation and its opening
+    IssueAttribute {
+        identity: String,
+        name: String,
+        value: u64,
+        /// Secret key of the issuer, as `<scheme>:<hex>`
+        issuer_key: SecretKey,
+    },
+    /// Attaches an attestation printed by `issue-attribute` to an identity
+    AddAttribute {
+        identity: String,
+        secret: Secret,
+        nonce: u32,
+        attestation: String,
+    },
+    /// Proves that an attribute of an identity satisfies a predicate, without revealing its value.
+    /// The identity also proves that it holds the account, like `verify-identity`.
+    ProveAttribute {
+        identity: String,
+        secret: Secret,
+        nonce: u32,
+        name: String,
+        issuer: PublicKey,
+        /// `attested`, `=<value>`, `>=<value>` or `<=<value>`
+        predicate: Predicate,
+        /// Opening printed by `issue-attribute`
+        opening: String,
+    },
 }
 
 #[tokio::main]
@@ -48,16 +222,25 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     let cli = Cli::parse();
-    let client = client_sdk::rest_client::NodeApiHttpClient::new(cli.host).unwrap();
+    let client = NodeApiHttpClient::new(cli.host).unwrap();
     let contract_name = &cli.contract_name;
 
     println!("🚀 Booting sp1...");
     let prover = SP1Prover::new(CONTRACT_ELF);
 
     match cli.command {
-        Commands::RegisterContract {} => {
+        Commands::RegisterContract {
+            iterations,
+            expiry,
+            grace_period,
+        } => {
             // Build initial state of contract
-            let initial_state = IdentityContractState::new();
+            let expiry = expiry.map(|period| ExpiryParams {
+                period,
+                grace_period,
+            });
+            let initial_state =
+                IdentityContractState::with_kdf(KdfParams { iterations }).with_expiry(expiry);
             println!("Initial state: {:?}", initial_state);
 
             let vk = serde_json::to_vec(&prover.vk).unwrap();
@@ -74,127 +257,652 @@ async fn main() -> anyhow::Result<()> {
             println!("✅ Register contract tx sent. Tx hash: {}", res);
         }
         Commands::RegisterIdentity { identity, password } => {
-            // Fetch the initial state from the node
-            let initial_state: IdentityContractState = client
-                .get_contract(&contract_name.clone().into())
-                .await
-                .unwrap()
-                .state
-                .into();
-
-            println!("Initial state {:?}", initial_state.clone());
-            println!("Identity {:?}", identity.clone());
-
-            // Build the blob transaction
             let action = IdentityAction::RegisterIdentity {
                 account: identity.clone(),
             };
-
-            let blobs = vec![sdk::Blob {
-                contract_name: contract_name.clone().into(),
-                data: sdk::BlobData(borsh::to_vec(&action).expect("failed to encode BlobData")),
-            }];
-            let blob_tx = BlobTransaction::new(identity, blobs.clone());
-
-            println!("blob_tx: {:#?}", blob_tx);
-            // Send the blob transaction
-            let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
-            println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
-
-            // Add delay to ensure blob transaction is processed
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-            // Build the contract input
-            let inputs = ContractInput {
-                state: initial_state.as_bytes().unwrap(),
-                identity: blob_tx.identity.clone(),
-                tx_hash: blob_tx_hash,
-                private_input: password.into_bytes().to_vec(),
-                blobs: blobs.clone(),
-                index: sdk::BlobIndex(0),
-                tx_ctx: None,
-            };
-
-            println!("inputs: {:#?}", inputs);
-
-            // Generate the zk proof
-            println!("🔍 Proving state transition...");
-            let proof = prover.prove(inputs).await.unwrap();
-
-            let proof_tx = ProofTransaction {
-                proof,
-                contract_name: contract_name.clone().into(),
+            let registration = Registration {
+                password,
+                salt: rand::random(),
             };
-
-            // Add delay before sending proof transaction
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-
-            // Send the proof transaction
-            let proof_tx_hash = client.send_tx_proof(&proof_tx).await.unwrap();
-            println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+            send_action(&client, &prover, contract_name, identity, action, |_, _| {
+                borsh::to_vec(&registration).expect("failed to encode registration")
+            })
+            .await;
         }
         Commands::VerifyIdentity {
             identity,
             password,
             nonce,
         } => {
-            // Fetch the initial state from the node
-            let initial_state: IdentityContractState = client
+            let action = IdentityAction::VerifyIdentity {
+                account: identity.clone(),
+                nonce,
+            };
+            send_action(&client, &prover, contract_name, identity, action, |_, _| {
+                password.into_bytes()
+            })
+            .await;
+        }
+        Commands::ChangePassword {
+            identity,
+            old_password,
+            new_password,
+            nonce,
+            iterations,
+        } => {
+            let action = IdentityAction::ChangePassword {
+                account: identity.clone(),
+                nonce,
+            };
+            let change = PasswordChange {
+                old_password,
+                new_password,
+                new_salt: rand::random(),
+                kdf: iterations.map(|iterations| KdfParams { iterations }),
+            };
+            send_action(&client, &prover, contract_name, identity, action, |_, _| {
+                borsh::to_vec(&change).expect("failed to encode password change")
+            })
+            .await;
+        }
+        Commands::RegisterKey {
+            identity,
+            secret_key,
+        } => {
+            let key = secret_key.public_key();
+            println!("Registering {} with key {}", identity, key);
+            let account = identity.clone();
+            let action = IdentityAction::RegisterKey {
+                account: account.clone(),
+                key,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    let authorization =
+                        Authorization::new(&account, 0, tx_hash, blobs, sdk::BlobIndex(0));
+                    secret_key.sign(&authorization.message())
+                },
+            )
+            .await;
+        }
+        Commands::VerifyKey {
+            identity,
+            secret_key,
+            nonce,
+        } => {
+            let account = identity.clone();
+            let action = IdentityAction::VerifyIdentity {
+                account: account.clone(),
+                nonce,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    let authorization =
+                        Authorization::new(&account, nonce, tx_hash, blobs, sdk::BlobIndex(0));
+                    secret_key.sign(&authorization.message())
+                },
+            )
+            .await;
+        }
+        Commands::SetGuardians {
+            identity,
+            secret,
+            nonce,
+            threshold,
+            guardians,
+        } => {
+            let account = identity.clone();
+            let action = IdentityAction::SetGuardians {
+                account: account.clone(),
+                nonce,
+                guardians: guardians.into_iter().collect(),
+                threshold,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ))
+                },
+            )
+            .await;
+        }
+        Commands::RecoveryCredential { secret, salt } => {
+            let state: IdentityContractState = client
                 .get_contract(&contract_name.clone().into())
                 .await
                 .unwrap()
                 .state
                 .into();
-
-            // Build the blob transaction
+            let salt = parse_salt(salt);
+            let credential = secret.new_credential(salt).credential(state.kdf());
+            if let Secret::Password(_) = secret {
+                println!("Salt: {}", hex::encode(salt));
+            }
+            println!("Credential: {}", hex::encode(credential.digest()));
+        }
+        Commands::ApproveRecovery {
+            identity,
+            guardian,
+            guardian_secret,
+            nonce,
+            credential,
+        } => {
+            let credential = hex::decode(credential)
+                .expect("invalid credential digest")
+                .try_into()
+                .expect("a credential digest is 32 bytes long");
+            let action = IdentityAction::ApproveRecovery {
+                account: identity,
+                guardian: guardian.clone(),
+                nonce,
+                credential,
+            };
+            // The transaction is sent by the guardian
+            let account = guardian.clone();
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                guardian,
+                action,
+                |tx_hash, blobs| {
+                    guardian_secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ))
+                },
+            )
+            .await;
+        }
+        Commands::CompleteRecovery {
+            identity,
+            secret,
+            salt,
+        } => {
+            if let (Secret::Password(_), None) = (&secret, &salt) {
+                panic!("the salt printed by recovery-credential is needed for a new password");
+            }
+            let credential = secret.new_credential(parse_salt(salt));
+            let action = IdentityAction::CompleteRecovery {
+                account: identity.clone(),
+            };
+            send_action(&client, &prover, contract_name, identity, action, |_, _| {
+                borsh::to_vec(&credential).expect("failed to encode credential")
+            })
+            .await;
+        }
+        Commands::CreateSession {
+            identity,
+            secret,
+            nonce,
+            session_key,
+            contracts,
+            blocks,
+        } => {
+            let key = session_key.public_key();
+            let expires_at = client.get_block_height().await.unwrap().0 + blocks;
+            println!("Creating session {} until block {}", key, expires_at);
+            let account = identity.clone();
+            let action = IdentityAction::CreateSession {
+                account: account.clone(),
+                nonce,
+                key,
+                expires_at,
+                contracts: contracts.into_iter().collect(),
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ))
+                },
+            )
+            .await;
+        }
+        Commands::RevokeSession {
+            identity,
+            secret,
+            nonce,
+            session,
+        } => {
+            let account = identity.clone();
+            let action = IdentityAction::RevokeSession {
+                account: account.clone(),
+                nonce,
+                key: session,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ))
+                },
+            )
+            .await;
+        }
+        Commands::VerifySession {
+            identity,
+            session_key,
+            nonce,
+        } => {
+            let account = identity.clone();
+            let action = IdentityAction::VerifySession {
+                account: account.clone(),
+                nonce,
+                key: session_key.public_key(),
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    let authorization =
+                        Authorization::new(&account, nonce, tx_hash, blobs, sdk::BlobIndex(0));
+                    session_key.sign(&authorization.message())
+                },
+            )
+            .await;
+        }
+        Commands::RegisterMultisig {
+            identity,
+            threshold,
+            members,
+            signers,
+        } => {
+            let action = IdentityAction::RegisterMultisig {
+                account: identity.clone(),
+                members,
+                threshold,
+            };
+            send_multisig_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                0,
+                action,
+                signers,
+            )
+            .await;
+        }
+        Commands::VerifyMultisig {
+            identity,
+            nonce,
+            signers,
+        } => {
             let action = IdentityAction::VerifyIdentity {
                 account: identity.clone(),
                 nonce,
             };
-
-            let blobs = vec![sdk::Blob {
-                contract_name: contract_name.clone().into(),
-                data: sdk::BlobData(borsh::to_vec(&action).expect("failed to encode BlobData")),
-            }];
-            let blob_tx = BlobTransaction::new(identity.clone(), blobs.clone());
-
-            println!("blob_tx: {:#?}", blob_tx);
-            // Send the blob transaction
-            let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
-            println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
-
-            // Add delay to ensure blob transaction is processed
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-            // Build the contract input
-            let inputs = ContractInput {
-                state: initial_state.as_bytes().unwrap(),
-                identity: blob_tx.identity.clone(),
-                tx_hash: blob_tx_hash,
-                private_input: password.into_bytes().to_vec(),
-                blobs: blobs.clone(),
-                index: sdk::BlobIndex(0),
-                tx_ctx: None,
+            send_multisig_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                nonce,
+                action,
+                signers,
+            )
+            .await;
+        }
+        Commands::AddMember {
+            identity,
+            nonce,
+            member,
+            signers,
+        } => {
+            let action = IdentityAction::AddMember {
+                account: identity.clone(),
+                nonce,
+                key: member,
+            };
+            send_multisig_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                nonce,
+                action,
+                signers,
+            )
+            .await;
+        }
+        Commands::RemoveMember {
+            identity,
+            nonce,
+            member,
+            signers,
+        } => {
+            let action = IdentityAction::RemoveMember {
+                account: identity.clone(),
+                nonce,
+                key: member,
+            };
+            send_multisig_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                nonce,
+                action,
+                signers,
+            )
+            .await;
+        }
+        Commands::DeleteIdentity {
+            identity,
+            secret,
+            nonce,
+        } => {
+            let account = identity.clone();
+            let action = IdentityAction::DeleteIdentity {
+                account: account.clone(),
+                nonce,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ))
+                },
+            )
+            .await;
+        }
+        Commands::RenewIdentity {
+            identity,
+            secret,
+            nonce,
+        } => {
+            let account = identity.clone();
+            let action = IdentityAction::RenewIdentity {
+                account: account.clone(),
+                nonce,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ))
+                },
+            )
+            .await;
+        }
+        Commands::IssueAttribute {
+            identity,
+            name,
+            value,
+            issuer_key,
+        } => {
+            let opening = AttributeOpening {
+                value,
+                salt: rand::random(),
+            };
+            let attestation = Attestation::issue(&issuer_key, &identity, &name, &opening);
+            println!(
+                "Attestation: {}",
+                hex::encode(borsh::to_vec(&attestation).unwrap())
+            );
+            println!("Opening: {}", hex::encode(borsh::to_vec(&opening).unwrap()));
+        }
+        Commands::AddAttribute {
+            identity,
+            secret,
+            nonce,
+            attestation,
+        } => {
+            let attestation: Attestation =
+                borsh::from_slice(&hex::decode(attestation).expect("invalid attestation"))
+                    .expect("failed to decode attestation");
+            let account = identity.clone();
+            let action = IdentityAction::AddAttribute {
+                account: account.clone(),
+                nonce,
+                attestation,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ))
+                },
+            )
+            .await;
+        }
+        Commands::ProveAttribute {
+            identity,
+            secret,
+            nonce,
+            name,
+            issuer,
+            predicate,
+            opening,
+        } => {
+            let opening: AttributeOpening =
+                borsh::from_slice(&hex::decode(opening).expect("invalid opening"))
+                    .expect("failed to decode opening");
+            let account = identity.clone();
+            let action = IdentityAction::ProveAttribute {
+                account: account.clone(),
+                nonce,
+                attribute: AttributeQuery {
+                    name,
+                    issuer,
+                    predicate,
+                },
             };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                identity,
+                action,
+                |tx_hash, blobs| {
+                    let credential = secret.prove(&Authorization::new(
+                        &account,
+                        nonce,
+                        tx_hash,
+                        blobs,
+                        sdk::BlobIndex(0),
+                    ));
+                    borsh::to_vec(&AttributeProof {
+                        opening,
+                        credential,
+                    })
+                    .expect("failed to encode attribute proof")
+                },
+            )
+            .await;
+        }
+    }
+    Ok(())
+}
 
-            println!("inputs: {:#?}", inputs);
+/// Parses a hex salt, or draws one at random.
+fn parse_salt(salt: Option<String>) -> [u8; SALT_LEN] {
+    match salt {
+        Some(salt) => hex::decode(salt)
+            .expect("invalid salt")
+            .try_into()
+            .expect("a salt is 16 bytes long"),
+        None => rand::random(),
+    }
+}
 
-            // Generate the zk proof
-            println!("🔍 Proving state transition...");
-            let proof = prover.prove(inputs).await.unwrap();
+/// Sends a blob transaction for `action` on behalf of `identity`, then proves and sends its proof.
+async fn send_action(
+    client: &NodeApiHttpClient,
+    prover: &SP1Prover,
+    contract_name: &str,
+    identity: String,
+    action: IdentityAction,
+    private_input: impl FnOnce(&TxHash, &[sdk::Blob]) -> Vec<u8>,
+) {
+    // Fetch the initial state from the node
+    let initial_state: IdentityContractState = client
+        .get_contract(&contract_name.into())
+        .await
+        .unwrap()
+        .state
+        .into();
+
+    // Build the blob transaction
+    let blobs = vec![sdk::Blob {
+        contract_name: contract_name.into(),
+        data: sdk::BlobData(borsh::to_vec(&action).expect("failed to encode BlobData")),
+    }];
+    let blob_tx = BlobTransaction::new(identity, blobs.clone());
+
+    println!("blob_tx: {:#?}", blob_tx);
+    // Send the blob transaction
+    let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
+    println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
+
+    // The context of the transaction is known once it is included in a block,
+    // sessions expire at the height of this block
+    let tx_ctx = fetch_tx_ctx(client, &blob_tx_hash).await;
+
+    // Build the contract input, whose private input may sign the blob transaction
+    let private_input = private_input(&blob_tx_hash, &blobs);
+    let inputs = ContractInput {
+        state: initial_state.as_bytes().unwrap(),
+        identity: blob_tx.identity.clone(),
+        tx_hash: blob_tx_hash,
+        private_input,
+        blobs: blobs.clone(),
+        index: sdk::BlobIndex(0),
+        tx_ctx: Some(tx_ctx),
+    };
+
+    print_output(&initial_state, &inputs);
+
+    // Generate the zk proof
+    println!("🔍 Proving state transition...");
+    let proof = prover.prove(inputs).await.unwrap();
+
+    let proof_tx = ProofTransaction {
+        proof,
+        contract_name: contract_name.into(),
+    };
+
+    // Add delay before sending proof transaction
+    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+
+    // Send the proof transaction
+    let proof_tx_hash = client.send_tx_proof(&proof_tx).await.unwrap();
+    println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+}
 
-            let proof_tx = ProofTransaction {
-                proof,
-                contract_name: contract_name.clone().into(),
-            };
+/// Sends an action of a multisig identity, whose private input holds the signatures of its `signers`.
+async fn send_multisig_action(
+    client: &NodeApiHttpClient,
+    prover: &SP1Prover,
+    contract_name: &str,
+    identity: String,
+    nonce: u32,
+    action: IdentityAction,
+    signers: Vec<SecretKey>,
+) {
+    let account = identity.clone();
+    send_action(
+        client,
+        prover,
+        contract_name,
+        identity,
+        action,
+        |tx_hash, blobs| {
+            let authorization =
+                Authorization::new(&account, nonce, tx_hash, blobs, sdk::BlobIndex(0));
+            let signatures = MultisigSignatures::sign(&signers, &authorization);
+            borsh::to_vec(&signatures).expect("failed to encode signatures")
+        },
+    )
+    .await;
+}
 
-            // Add delay before sending proof transaction
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+/// Executes the contract locally, then decodes and prints the event it emits.
+fn print_output(initial_state: &IdentityContractState, inputs: &ContractInput) {
+    let res = initial_state.clone().execute(inputs).unwrap();
+    let output = IdentityOutput::decode(&res.0).expect("failed to decode program output");
+    println!("🚀 Executed: {}", output.event());
+}
 
-            // Send the proof transaction
-            let proof_tx_hash = client.send_tx_proof(&proof_tx).await.unwrap();
-            println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+/// Waits for the blob transaction to be included in a block, and returns its context.
+async fn fetch_tx_ctx(client: &NodeApiHttpClient, tx_hash: &TxHash) -> sdk::TxContext {
+    for _ in 0..30 {
+        if let Ok(tx) = client.get_unsettled_tx(tx_hash).await {
+            return tx.tx_context;
         }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     }
-    Ok(())
+    panic!("blob tx {} was not included in a block", tx_hash);
 }
@@ -0,0 +1,137 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::keys::{PublicKey, SecretKey};
+
+/// Attribute of an account, attested by an issuer. Only a commitment to its value is stored on-chain.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Attestation {
+    pub name: String,
+    pub issuer: PublicKey,
+    pub commitment: [u8; 32],
+    pub signature: Vec<u8>, // Signature by the issuer of the `message` of the attestation
+}
+
+impl Attestation {
+    /// Attests the attribute `name` of `account`, of the value opened by `opening`.
+    pub fn issue(
+        issuer: &SecretKey,
+        account: &str,
+        name: &str,
+        opening: &AttributeOpening,
+    ) -> Self {
+        let commitment = opening.commitment();
+        Attestation {
+            name: name.to_string(),
+            issuer: issuer.public_key(),
+            commitment,
+            signature: issuer.sign(&Self::message(account, name, &commitment)),
+        }
+    }
+
+    /// Message signed by the issuer: the SHA-256 of the borsh-encoded account, name and commitment,
+    /// so that an attestation cannot be attached to another account nor attribute.
+    pub fn message(account: &str, name: &str, commitment: &[u8; 32]) -> [u8; 32] {
+        let statement = borsh::to_vec(&(account, name, commitment))
+            .expect("Failed to encode attribute statement");
+        Sha256::digest(statement).into()
+    }
+
+    /// Checks that the issuer signed this attestation for `account`.
+    pub fn verify(&self, account: &str) -> Result<(), String> {
+        self.issuer
+            .verify(
+                &Self::message(account, &self.name, &self.commitment),
+                &self.signature,
+            )
+            .map_err(|_| "Invalid issuer signature".to_string())
+    }
+}
+
+/// Value of an attribute, and the random salt hiding it in its commitment. The issuer gives it to the
+/// account, which sends it as private input to prove a predicate on the value.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct AttributeOpening {
+    pub value: u64,
+    pub salt: [u8; 32],
+}
+
+impl AttributeOpening {
+    pub fn commitment(&self) -> [u8; 32] {
+        Sha256::digest(borsh::to_vec(self).expect("Failed to encode AttributeOpening")).into()
+    }
+}
+
+/// Predicate on the value of an attribute
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum Predicate {
+    /// The attribute is attested, whatever its value
+    Attested,
+    Equals(u64),
+    AtLeast(u64),
+    AtMost(u64),
+}
+
+impl Predicate {
+    pub fn holds(&self, value: u64) -> bool {
+        match self {
+            Predicate::Attested => true,
+            Predicate::Equals(expected) => value == *expected,
+            Predicate::AtLeast(min) => value >= *min,
+            Predicate::AtMost(max) => value <= *max,
+        }
+    }
+}
+
+impl std::fmt::Display for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Predicate::Attested => write!(f, "attested"),
+            Predicate::Equals(value) => write!(f, "={value}"),
+            Predicate::AtLeast(value) => write!(f, ">={value}"),
+            Predicate::AtMost(value) => write!(f, "<={value}"),
+        }
+    }
+}
+
+/// Parses a predicate as displayed: `attested`, `=<value>`, `>=<value>` or `<=<value>`.
+impl std::str::FromStr for Predicate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |value: &str| value.parse::<u64>().map_err(|e| e.to_string());
+        if s == "attested" {
+            Ok(Predicate::Attested)
+        } else if let Some(value) = s.strip_prefix(">=") {
+            Ok(Predicate::AtLeast(parse(value)?))
+        } else if let Some(value) = s.strip_prefix("<=") {
+            Ok(Predicate::AtMost(parse(value)?))
+        } else if let Some(value) = s.strip_prefix('=') {
+            Ok(Predicate::Equals(parse(value)?))
+        } else {
+            Err(format!("Unknown predicate {s}"))
+        }
+    }
+}
+
+/// Predicate on the attribute `name` of an account, as attested by `issuer`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct AttributeQuery {
+    pub name: String,
+    pub issuer: PublicKey,
+    pub predicate: Predicate,
+}
+
+impl std::fmt::Display for AttributeQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.predicate {
+            Predicate::Attested => write!(f, "{} attested by {}", self.name, self.issuer),
+            _ => write!(
+                f,
+                "{} {} attested by {}",
+                self.name, self.predicate, self.issuer
+            ),
+        }
+    }
+}
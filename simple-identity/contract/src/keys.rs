@@ -0,0 +1,190 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use k256::ecdsa::{signature::Signer, signature::Verifier, RecoveryId};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Public key of an account. Instead of a password, the account proves its identity with a signature,
+/// sent as private input and checked in the guest.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum PublicKey {
+    Ed25519([u8; 32]),
+    /// SEC1-encoded key, verifying ECDSA signatures of the SHA-256 of the message
+    Secp256k1(Vec<u8>),
+    /// Ethereum address, recovered from `personal_sign` signatures of the message, as made by wallets
+    Ethereum([u8; 20]),
+}
+
+impl PublicKey {
+    /// Checks that `signature` is a signature of `message` by this key.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), String> {
+        match self {
+            PublicKey::Ed25519(key) => {
+                let key = ed25519_dalek::VerifyingKey::from_bytes(key)
+                    .map_err(|_| "Invalid ed25519 public key".to_string())?;
+                let signature = ed25519_dalek::Signature::from_slice(signature)
+                    .map_err(|_| "Invalid ed25519 signature".to_string())?;
+                key.verify_strict(message, &signature)
+                    .map_err(|_| "Invalid signature".to_string())
+            }
+            PublicKey::Secp256k1(key) => {
+                let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                    .map_err(|_| "Invalid secp256k1 public key".to_string())?;
+                let signature = k256::ecdsa::Signature::from_slice(signature)
+                    .map_err(|_| "Invalid secp256k1 signature".to_string())?;
+                key.verify(message, &signature)
+                    .map_err(|_| "Invalid signature".to_string())
+            }
+            PublicKey::Ethereum(address) => {
+                if recover_eth_address(message, signature)? != *address {
+                    return Err("Invalid signature".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PublicKey::Ed25519(key) => write!(f, "ed25519:{}", hex::encode(key)),
+            PublicKey::Secp256k1(key) => write!(f, "secp256k1:{}", hex::encode(key)),
+            PublicKey::Ethereum(address) => write!(f, "0x{}", hex::encode(address)),
+        }
+    }
+}
+
+/// Parses a public key as displayed: `ed25519:<hex>`, `secp256k1:<hex>` or a `0x` Ethereum address.
+impl std::str::FromStr for PublicKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(address) = s.strip_prefix("0x") {
+            let address = hex::decode(address).map_err(|e| e.to_string())?;
+            return Ok(PublicKey::Ethereum(address.try_into().map_err(|_| {
+                "An Ethereum address is 20 bytes long".to_string()
+            })?));
+        }
+        let (scheme, key) = s
+            .split_once(':')
+            .ok_or("Expected <scheme>:<hex public key>".to_string())?;
+        let key = hex::decode(key).map_err(|e| e.to_string())?;
+        match scheme {
+            "ed25519" => {
+                Ok(PublicKey::Ed25519(key.try_into().map_err(|_| {
+                    "An ed25519 public key is 32 bytes long".to_string()
+                })?))
+            }
+            "secp256k1" => Ok(PublicKey::Secp256k1(key)),
+            _ => Err(format!("Unknown key scheme {scheme}")),
+        }
+    }
+}
+
+/// Secret key matching a `PublicKey`, used by clients to sign messages.
+#[derive(Debug, Clone)]
+pub enum SecretKey {
+    Ed25519(ed25519_dalek::SigningKey),
+    Secp256k1(k256::ecdsa::SigningKey),
+    Ethereum(k256::ecdsa::SigningKey),
+}
+
+impl SecretKey {
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            SecretKey::Ed25519(key) => PublicKey::Ed25519(key.verifying_key().to_bytes()),
+            SecretKey::Secp256k1(key) => PublicKey::Secp256k1(
+                key.verifying_key()
+                    .to_encoded_point(true)
+                    .as_bytes()
+                    .to_vec(),
+            ),
+            SecretKey::Ethereum(key) => PublicKey::Ethereum(eth_address(key.verifying_key())),
+        }
+    }
+
+    /// Signs `message`, in the format checked by `PublicKey::verify`.
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            SecretKey::Ed25519(key) => key.sign(message).to_bytes().to_vec(),
+            SecretKey::Secp256k1(key) => {
+                let signature: k256::ecdsa::Signature = key.sign(message);
+                signature.to_bytes().to_vec()
+            }
+            SecretKey::Ethereum(key) => {
+                let (signature, recovery_id) = key
+                    .sign_prehash_recoverable(&eth_message_hash(message))
+                    .expect("failed to sign message");
+                let mut bytes = signature.to_bytes().to_vec();
+                // Wallets add 27 to the recovery id, as in legacy Ethereum transactions
+                bytes.push(recovery_id.to_byte() + 27);
+                bytes
+            }
+        }
+    }
+}
+
+/// Parses `<scheme>:<hex secret key>`, the scheme being `ed25519`, `secp256k1` or `ethereum`.
+impl std::str::FromStr for SecretKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, key) = s
+            .split_once(':')
+            .ok_or("Expected <scheme>:<hex secret key>".to_string())?;
+        let key = hex::decode(key).map_err(|e| e.to_string())?;
+        match scheme {
+            "ed25519" => {
+                let key: [u8; 32] = key
+                    .try_into()
+                    .map_err(|_| "An ed25519 secret key is 32 bytes long".to_string())?;
+                Ok(SecretKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(
+                    &key,
+                )))
+            }
+            "secp256k1" | "ethereum" => {
+                let key = k256::ecdsa::SigningKey::from_slice(&key)
+                    .map_err(|_| "Invalid secp256k1 secret key".to_string())?;
+                if scheme == "ethereum" {
+                    Ok(SecretKey::Ethereum(key))
+                } else {
+                    Ok(SecretKey::Secp256k1(key))
+                }
+            }
+            _ => Err(format!("Unknown key scheme {scheme}")),
+        }
+    }
+}
+
+/// Hash signed by Ethereum wallets for `personal_sign`.
+pub fn eth_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// Ethereum address of a key: the last 20 bytes of the keccak256 of its uncompressed encoding.
+pub fn eth_address(key: &k256::ecdsa::VerifyingKey) -> [u8; 20] {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    hash[12..].try_into().expect("an address is 20 bytes long")
+}
+
+/// Recovers the address of the key that signed `message`, from a 65-byte `r || s || v` signature.
+pub fn recover_eth_address(message: &[u8], signature: &[u8]) -> Result<[u8; 20], String> {
+    let [rs @ .., v] = signature else {
+        return Err("Invalid Ethereum signature".to_string());
+    };
+    let signature = k256::ecdsa::Signature::from_slice(rs)
+        .map_err(|_| "Invalid Ethereum signature".to_string())?;
+    let recovery_id = RecoveryId::from_byte(v.checked_sub(27).unwrap_or(*v))
+        .ok_or("Invalid recovery id".to_string())?;
+    let key = k256::ecdsa::VerifyingKey::recover_from_prehash(
+        &eth_message_hash(message),
+        &signature,
+        recovery_id,
+    )
+    .map_err(|_| "Invalid signature".to_string())?;
+    Ok(eth_address(&key))
+}
@@ -1,11 +1,17 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use borsh::{io::Error, BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
-use sdk::RunResult;
+use sdk::{Blob, BlobIndex, ContractName, RunResult, TxHash};
 use sha2::{Digest, Sha256};
 
+pub mod attributes;
+pub mod keys;
+
+use attributes::{Attestation, AttributeOpening, AttributeQuery};
+use keys::{PublicKey, SecretKey};
+
 impl sdk::HyleContract for IdentityContractState {
     /// Entry point of the contract's logic
     fn execute(&mut self, contract_input: &sdk::ContractInput) -> RunResult {
@@ -13,19 +19,147 @@ impl sdk::HyleContract for IdentityContractState {
         let (action, ctx) = sdk::utils::parse_raw_contract_input::<IdentityAction>(contract_input)?;
 
         // Extract private information
-        let password = core::str::from_utf8(&contract_input.private_input).unwrap();
+        let private_input = &contract_input.private_input;
+
+        // Sessions and accounts expire at a given height of the block including the transaction
+        self.block_height = contract_input
+            .tx_ctx
+            .as_ref()
+            .map(|tx_ctx| tx_ctx.block_height.0);
+
+        // What the private input of `account` authorizes, when it proves its identity
+        let authorize = |account: &str, nonce: u32| {
+            Authorization::new(
+                account,
+                nonce,
+                &contract_input.tx_hash,
+                &contract_input.blobs,
+                contract_input.index,
+            )
+        };
 
         // Execute the given action
-        let res = match action {
+        let event = match action {
             IdentityAction::RegisterIdentity { account } => {
-                self.register_identity(&account, password)?
+                let registration: Registration = borsh::from_slice(private_input)
+                    .map_err(|_| "Failed to decode registration".to_string())?;
+                let credential =
+                    Credential::password(&registration.password, registration.salt, &self.kdf);
+                self.register_identity(&account, credential)?
+            }
+            IdentityAction::RegisterKey { account, key } => {
+                // Registering proves the ownership of the key, so that no one registers the key of someone else
+                key.verify(&authorize(&account, 0).message(), private_input)?;
+                self.register_identity(&account, Credential::PublicKey(key))?
+            }
+            IdentityAction::RegisterMultisig {
+                account,
+                members,
+                threshold,
+            } => {
+                let credential = Credential::multisig(members, threshold)?;
+                // As for a key, `threshold` members prove that they agree to hold the account
+                credential.check_signatures(&authorize(&account, 0).message(), private_input)?;
+                self.register_identity(&account, credential)?
             }
             IdentityAction::VerifyIdentity { account, nonce } => {
-                self.verify_identity(&account, nonce, password)?
+                self.verify_identity(&authorize(&account, nonce), private_input)?
+            }
+            IdentityAction::ChangePassword { account, nonce } => {
+                let change: PasswordChange = borsh::from_slice(private_input)
+                    .map_err(|_| "Failed to decode password change".to_string())?;
+                self.change_password(&account, nonce, &change)?
+            }
+            IdentityAction::SetGuardians {
+                account,
+                nonce,
+                guardians,
+                threshold,
+            } => self.set_guardians(
+                &authorize(&account, nonce),
+                private_input,
+                guardians,
+                threshold,
+            )?,
+            IdentityAction::ApproveRecovery {
+                account,
+                guardian,
+                nonce,
+                credential,
+            } => self.approve_recovery(
+                &account,
+                &authorize(&guardian, nonce),
+                private_input,
+                credential,
+            )?,
+            IdentityAction::CompleteRecovery { account } => {
+                let credential: NewCredential = borsh::from_slice(private_input)
+                    .map_err(|_| "Failed to decode new credential".to_string())?;
+                self.complete_recovery(&account, credential)?
+            }
+            IdentityAction::CreateSession {
+                account,
+                nonce,
+                key,
+                expires_at,
+                contracts,
+            } => {
+                let session = Session {
+                    key,
+                    expires_at,
+                    contracts,
+                };
+                self.create_session(&authorize(&account, nonce), private_input, session)?
+            }
+            IdentityAction::RevokeSession {
+                account,
+                nonce,
+                key,
+            } => self.revoke_session(&authorize(&account, nonce), private_input, key)?,
+            IdentityAction::VerifySession {
+                account,
+                nonce,
+                key,
+            } => self.verify_session(&authorize(&account, nonce), &key, private_input)?,
+            IdentityAction::AddMember {
+                account,
+                nonce,
+                key,
+            } => self.add_member(&authorize(&account, nonce), private_input, key)?,
+            IdentityAction::RemoveMember {
+                account,
+                nonce,
+                key,
+            } => self.remove_member(&authorize(&account, nonce), private_input, key)?,
+            IdentityAction::DeleteIdentity { account, nonce } => {
+                self.delete_identity(&authorize(&account, nonce), private_input)?
+            }
+            IdentityAction::RenewIdentity { account, nonce } => {
+                self.renew_identity(&authorize(&account, nonce), private_input)?
+            }
+            IdentityAction::AddAttribute {
+                account,
+                nonce,
+                attestation,
+            } => self.add_attribute(&authorize(&account, nonce), private_input, attestation)?,
+            IdentityAction::ProveAttribute {
+                account,
+                nonce,
+                attribute,
+            } => {
+                // The attribute is proven for the identity of the transaction, that other blobs gate on
+                if contract_input.identity.0 != account {
+                    return Err(format!(
+                        "Attributes of {account} can only be proven in its own transactions"
+                    ));
+                }
+                let proof: AttributeProof = borsh::from_slice(private_input)
+                    .map_err(|_| "Failed to decode attribute proof".to_string())?;
+                self.prove_attribute(&authorize(&account, nonce), attribute, &proof)?
             }
         };
 
-        Ok((res, ctx, vec![]))
+        Ok((IdentityOutput::V1(event).encode(), ctx, vec![]))
     }
 
     /// In this example, we serialize the full state on-chain.
@@ -34,34 +168,521 @@ impl sdk::HyleContract for IdentityContractState {
     }
 }
 
+/// Length of the random salt of each account
+pub const SALT_LEN: usize = 16;
+
+/// Iterations of PBKDF2 used by default for new passwords
+pub const DEFAULT_ITERATIONS: u32 = 100_000;
+
+/// Parameters of the key derivation function hashing the passwords: PBKDF2 with HMAC-SHA256.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct KdfParams {
+    pub iterations: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            iterations: DEFAULT_ITERATIONS,
+        }
+    }
+}
+
+/// Expiry of the accounts. Registered names are released once expired, so that they are not squatted forever.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ExpiryParams {
+    pub period: u64, // Blocks an account is valid for, after its registration or renewal
+    pub grace_period: u64, // Blocks after its expiry during which only the owner can renew the account
+}
+
+/// What an account proves the knowledge of, with the private input of its verifications
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum Credential {
+    /// The private input is the password, stored hashed
+    Password {
+        hash: String,
+        salt: [u8; SALT_LEN],
+        kdf: KdfParams, // Parameters the hash was derived with
+    },
+    /// The private input is a signature by the key of the `Authorization` of the verification
+    PublicKey(PublicKey),
+    /// The private input is a `MultisigSignatures`, holding signatures of the `Authorization` of the
+    /// verification by at least `threshold` of the `members`
+    Multisig {
+        members: Vec<PublicKey>,
+        threshold: u32,
+    },
+}
+
+/// Credential set by a registration or a recovery, as sent in private input.
+/// Passwords are hashed with the parameters of the contract.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum NewCredential {
+    Password(Registration),
+    PublicKey(PublicKey),
+}
+
+/// Guardians of an account, who can give it a new credential once `threshold` of them approve it
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Guardians {
+    pub members: BTreeSet<String>,
+    pub threshold: u32,
+    pub approvals: BTreeMap<String, [u8; 32]>, // Digest of the credential approved by each guardian
+}
+
+/// Short-lived key to which an account delegates its verifications, so that clients do not keep
+/// sending its password. It is valid until the block `expires_at`, in transactions whose other blobs
+/// are all on the `contracts` of its scope.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Session {
+    pub key: PublicKey,
+    pub expires_at: u64,
+    pub contracts: BTreeSet<String>,
+}
+
 /// Struct to hold account's information
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct AccountInfo {
-    pub hash: String,
+    pub credential: Credential,
     pub nonce: u32,
+    pub guardians: Option<Guardians>,
+    pub sessions: Vec<Session>,
+    pub expires_at: Option<u64>, // Height of the first block at which the account is expired
+    pub attributes: Vec<Attestation>,
 }
 
 /// The state of the contract, that is totally serialized on-chain
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
 pub struct IdentityContractState {
     identities: BTreeMap<String, AccountInfo>,
+    /// Parameters used for new passwords, fixed when the contract is registered. Each password keeps
+    /// the parameters it was hashed with, which its owner can raise when changing it.
+    kdf: KdfParams,
+    expiry: Option<ExpiryParams>, // Accounts never expire if not set
+    /// Height of the block including the transaction being executed, taken from its context.
+    /// It is not part of the on-chain state.
+    #[borsh(skip)]
+    #[serde(skip)]
+    block_height: Option<u64>,
 }
 
 /// Enum representing the actions that can be performed by the IdentityVerification contract.
 #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum IdentityAction {
-    RegisterIdentity { account: String },
-    VerifyIdentity { account: String, nonce: u32 },
+    RegisterIdentity {
+        account: String,
+    },
+    /// Registers an account proving its identity with signatures of `key` instead of a password.
+    /// The private input is a signature of the `Authorization` of the registration, with nonce 0.
+    RegisterKey {
+        account: String,
+        key: PublicKey,
+    },
+    VerifyIdentity {
+        account: String,
+        nonce: u32,
+    },
+    /// Replaces the password of the account, the old and new passwords are sent as a `PasswordChange` private input
+    ChangePassword {
+        account: String,
+        nonce: u32,
+    },
+    /// Sets the guardians able to recover the account, proving its identity like `VerifyIdentity`
+    SetGuardians {
+        account: String,
+        nonce: u32,
+        guardians: BTreeSet<String>,
+        threshold: u32,
+    },
+    /// Approves giving `account` the credential of digest `credential`. The guardian proves its
+    /// identity like `VerifyIdentity`, with its own nonce.
+    ApproveRecovery {
+        account: String,
+        guardian: String,
+        nonce: u32,
+        credential: [u8; 32],
+    },
+    /// Gives `account` the credential sent as a `NewCredential` private input, once enough guardians approved it
+    CompleteRecovery {
+        account: String,
+    },
+    /// Delegates the verifications of the account to a session `key`, until the block `expires_at` and on
+    /// `contracts` only. The account proves its identity like `VerifyIdentity`.
+    CreateSession {
+        account: String,
+        nonce: u32,
+        key: PublicKey,
+        expires_at: u64,
+        contracts: BTreeSet<String>,
+    },
+    /// Revokes the session of `key`, the account proving its identity like `VerifyIdentity`
+    RevokeSession {
+        account: String,
+        nonce: u32,
+        key: PublicKey,
+    },
+    /// Verifies the identity of the account with the session `key`.
+    /// The private input is a signature of the `Authorization` of the verification by the session key.
+    VerifySession {
+        account: String,
+        nonce: u32,
+        key: PublicKey,
+    },
+    /// Registers an account held by `threshold` of the `members`. The private input is a `MultisigSignatures`
+    /// of the `Authorization` of the registration, with nonce 0, by `threshold` members.
+    RegisterMultisig {
+        account: String,
+        members: Vec<PublicKey>,
+        threshold: u32,
+    },
+    /// Adds a member to a multisig account, `threshold` members signing like for `VerifyIdentity`
+    AddMember {
+        account: String,
+        nonce: u32,
+        key: PublicKey,
+    },
+    /// Removes a member from a multisig account, `threshold` members signing like for `VerifyIdentity`
+    RemoveMember {
+        account: String,
+        nonce: u32,
+        key: PublicKey,
+    },
+    /// Deletes the account, proving its identity like `VerifyIdentity`. Its name can be registered again.
+    DeleteIdentity {
+        account: String,
+        nonce: u32,
+    },
+    /// Extends the expiry of the account by the period of the contract, proving its identity like
+    /// `VerifyIdentity`. An expired account can still be renewed, until someone else registers it.
+    RenewIdentity {
+        account: String,
+        nonce: u32,
+    },
+    /// Attaches an attribute attested by an issuer to the account, proving its identity like `VerifyIdentity`.
+    /// It replaces any attribute of the same name by the same issuer.
+    AddAttribute {
+        account: String,
+        nonce: u32,
+        attestation: Attestation,
+    },
+    /// Proves that an attested attribute of the account satisfies a predicate, without revealing its value.
+    /// The private input is an `AttributeProof`: the account proves its identity like `VerifyIdentity`, and
+    /// opens the attribute. The action fails if the predicate does not hold, so that other blobs of the
+    /// transaction can be gated on it.
+    ProveAttribute {
+        account: String,
+        nonce: u32,
+        attribute: AttributeQuery,
+    },
+}
+
+/// Private input of `RegisterIdentity`. The salt is drawn at random by the client.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Registration {
+    pub password: String,
+    pub salt: [u8; SALT_LEN],
+}
+
+/// Private input of `ChangePassword`, proving the knowledge of the old password
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PasswordChange {
+    pub old_password: String,
+    pub new_password: String,
+    pub new_salt: [u8; SALT_LEN],
+    /// Parameters of the new hash, the ones of the contract if not set. They cannot be weaker.
+    pub kdf: Option<KdfParams>,
+}
+
+/// Private input of `ProveAttribute`. Knowing the opening is not enough: it is known to the issuer, and
+/// to whoever the account gave it to, so the account also proves its identity.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AttributeProof {
+    pub opening: AttributeOpening,
+    pub credential: Vec<u8>, // Private input of a `VerifyIdentity` with the same authorization
+}
+
+/// Private input of the verifications of a multisig account: signatures of the `Authorization` by some of its members
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct MultisigSignatures(pub Vec<(PublicKey, Vec<u8>)>);
+
+impl MultisigSignatures {
+    /// Signatures of `authorization` by each of the `signers`.
+    pub fn sign(signers: &[SecretKey], authorization: &Authorization) -> Self {
+        let message = authorization.message();
+        MultisigSignatures(
+            signers
+                .iter()
+                .map(|signer| (signer.public_key(), signer.sign(&message)))
+                .collect(),
+        )
+    }
+}
+
+/// What a verification authorizes: the blob transaction, and the blobs of the other contracts in it.
+/// Key accounts sign its `message`, so that a signature made for a transaction cannot be replayed in
+/// another one, even by someone who knows the next nonce of the account.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Authorization {
+    pub contract_name: ContractName,
+    pub account: String,
+    pub nonce: u32,
+    pub tx_hash: TxHash,
+    pub blobs: Vec<(BlobIndex, Blob)>, // Blobs of the transaction, except the verification itself
+}
+
+impl Authorization {
+    /// Authorization of the verification at `index` among the `blobs` of the transaction `tx_hash`.
+    pub fn new(
+        account: &str,
+        nonce: u32,
+        tx_hash: &TxHash,
+        blobs: &[Blob],
+        index: BlobIndex,
+    ) -> Self {
+        Authorization {
+            contract_name: blobs[index.0].contract_name.clone(),
+            account: account.to_string(),
+            nonce,
+            tx_hash: tx_hash.clone(),
+            blobs: blobs
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index.0)
+                .map(|(i, blob)| (BlobIndex(i), blob.clone()))
+                .collect(),
+        }
+    }
+
+    /// Message signed by key accounts: the SHA-256 of the borsh-encoded authorization.
+    pub fn message(&self) -> [u8; 32] {
+        Sha256::digest(borsh::to_vec(self).expect("Failed to encode Authorization")).into()
+    }
+}
+
+/// Enum representing the events emitted by the IdentityVerification contract.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum IdentityEvent {
+    IdentityRegistered {
+        account: String,
+    },
+    /// The verification is bound to the transaction `tx_hash`, and commits to the message of its `Authorization`
+    IdentityVerified {
+        account: String,
+        nonce: u32,
+        tx_hash: TxHash,
+        authorization: [u8; 32],
+    },
+    PasswordChanged {
+        account: String,
+        nonce: u32,
+    },
+    GuardiansSet {
+        account: String,
+        guardians: BTreeSet<String>,
+        threshold: u32,
+    },
+    RecoveryApproved {
+        account: String,
+        guardian: String,
+        credential: [u8; 32],
+    },
+    RecoveryCompleted {
+        account: String,
+    },
+    SessionCreated {
+        account: String,
+        key: PublicKey,
+        expires_at: u64,
+        contracts: BTreeSet<String>,
+    },
+    SessionRevoked {
+        account: String,
+        key: PublicKey,
+    },
+    MemberAdded {
+        account: String,
+        key: PublicKey,
+    },
+    MemberRemoved {
+        account: String,
+        key: PublicKey,
+    },
+    IdentityDeleted {
+        account: String,
+    },
+    IdentityRenewed {
+        account: String,
+        expires_at: u64,
+    },
+    AttributeAdded {
+        account: String,
+        name: String,
+        issuer: PublicKey,
+    },
+    AttributeProven {
+        account: String,
+        attribute: AttributeQuery,
+    },
+}
+
+/// The program output of the contract: the event emitted by the executed action.
+/// It is versioned so that indexers can keep decoding the outputs of older versions of the contract.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum IdentityOutput {
+    V1(IdentityEvent),
+}
+
+impl std::fmt::Display for IdentityEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentityEvent::IdentityRegistered { account } => {
+                write!(f, "Registered identity for account {account}")
+            }
+            IdentityEvent::IdentityVerified {
+                account,
+                nonce,
+                tx_hash,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Verified identity of account {account} with nonce {nonce} for tx {tx_hash}"
+                )
+            }
+            IdentityEvent::PasswordChanged { account, nonce } => {
+                write!(
+                    f,
+                    "Changed password of account {account} with nonce {nonce}"
+                )
+            }
+            IdentityEvent::GuardiansSet {
+                account,
+                guardians,
+                threshold,
+            } => {
+                let guardians: Vec<&str> = guardians.iter().map(String::as_str).collect();
+                write!(
+                    f,
+                    "Set {threshold} of {} as guardians of account {account}",
+                    guardians.join(", ")
+                )
+            }
+            IdentityEvent::RecoveryApproved {
+                account,
+                guardian,
+                credential,
+            } => write!(
+                f,
+                "{guardian} approved the recovery of account {account} with credential {}",
+                hex::encode(credential)
+            ),
+            IdentityEvent::RecoveryCompleted { account } => {
+                write!(f, "Recovered account {account}")
+            }
+            IdentityEvent::SessionCreated {
+                account,
+                key,
+                expires_at,
+                contracts,
+            } => {
+                let contracts: Vec<&str> = contracts.iter().map(String::as_str).collect();
+                write!(
+                    f,
+                    "Created session {key} of account {account} on {} until block {expires_at}",
+                    contracts.join(", ")
+                )
+            }
+            IdentityEvent::SessionRevoked { account, key } => {
+                write!(f, "Revoked session {key} of account {account}")
+            }
+            IdentityEvent::MemberAdded { account, key } => {
+                write!(f, "Added member {key} to account {account}")
+            }
+            IdentityEvent::MemberRemoved { account, key } => {
+                write!(f, "Removed member {key} from account {account}")
+            }
+            IdentityEvent::IdentityDeleted { account } => {
+                write!(f, "Deleted identity of account {account}")
+            }
+            IdentityEvent::IdentityRenewed {
+                account,
+                expires_at,
+            } => write!(f, "Renewed account {account} until block {expires_at}"),
+            IdentityEvent::AttributeAdded {
+                account,
+                name,
+                issuer,
+            } => write!(
+                f,
+                "Added attribute {name} attested by {issuer} to account {account}"
+            ),
+            IdentityEvent::AttributeProven { account, attribute } => {
+                write!(f, "Proved {attribute} for account {account}")
+            }
+        }
+    }
+}
+
+impl IdentityEvent {
+    /// Event of a verification, bound to the transaction and committing to the authorization.
+    fn verified(authorization: &Authorization) -> Self {
+        IdentityEvent::IdentityVerified {
+            account: authorization.account.clone(),
+            nonce: authorization.nonce,
+            tx_hash: authorization.tx_hash.clone(),
+            authorization: authorization.message(),
+        }
+    }
+}
+
+impl IdentityOutput {
+    /// Encodes the output as the string returned by `execute`: the sdk expects a string,
+    /// so the borsh encoding is hex-encoded.
+    pub fn encode(&self) -> String {
+        hex::encode(borsh::to_vec(self).expect("Failed to encode IdentityOutput"))
+    }
+
+    pub fn decode(output: &str) -> Result<Self, String> {
+        let bytes = hex::decode(output).map_err(|e| e.to_string())?;
+        borsh::from_slice(&bytes).map_err(|e| e.to_string())
+    }
+
+    pub fn event(&self) -> &IdentityEvent {
+        match self {
+            IdentityOutput::V1(event) => event,
+        }
+    }
 }
 
 /// Some helper methods for the state
 impl IdentityContractState {
     pub fn new() -> Self {
+        Self::with_kdf(KdfParams::default())
+    }
+
+    pub fn with_kdf(kdf: KdfParams) -> Self {
         IdentityContractState {
             identities: BTreeMap::new(),
+            kdf,
+            expiry: None,
+            block_height: None,
         }
     }
 
+    /// Makes the accounts registered from now on expire, as set by `expiry`.
+    pub fn with_expiry(mut self, expiry: Option<ExpiryParams>) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    pub fn kdf(&self) -> &KdfParams {
+        &self.kdf
+    }
+
+    pub fn expiry(&self) -> Option<&ExpiryParams> {
+        self.expiry.as_ref()
+    }
+
     pub fn get_nonce(&self, username: &str) -> Result<u32, &'static str> {
         let info = self.identities.get(username).ok_or("Identity not found")?;
         Ok(info.nonce)
@@ -73,48 +694,682 @@ impl IdentityContractState {
 }
 
 impl IdentityContractState {
-    fn register_identity(&mut self, account: &str, private_input: &str) -> Result<String, String> {
-        let id = format!("{account}:{private_input}");
-        let mut hasher = Sha256::new();
-        hasher.update(id.as_bytes());
-        let hash_bytes = hasher.finalize();
+    /// Registers `account`, which must be free: never registered, deleted, or expired for longer
+    /// than the grace period.
+    fn register_identity(
+        &mut self,
+        account: &str,
+        credential: Credential,
+    ) -> Result<IdentityEvent, String> {
+        if let Some(stored_info) = self.identities.get(account) {
+            let grace_period = self.expiry.as_ref().map_or(0, |e| e.grace_period);
+            match stored_info.expires_at {
+                // A grace period reaching past the last block ends at the last block
+                Some(expires_at)
+                    if self.block_height()? >= expires_at.saturating_add(grace_period) =>
+                {
+                    self.release(account)
+                }
+                _ => return Err("Identity already exists".to_string()),
+            }
+        }
+
+        let expires_at = match &self.expiry {
+            Some(expiry) => Some(expires_after(self.block_height()?, expiry.period)?),
+            None => None,
+        };
         let account_info = AccountInfo {
-            hash: hex::encode(hash_bytes),
+            credential,
             nonce: 0,
+            guardians: None,
+            sessions: vec![],
+            expires_at,
+            attributes: vec![],
         };
+        self.identities.insert(account.to_string(), account_info);
+        Ok(IdentityEvent::IdentityRegistered {
+            account: account.to_string(),
+        })
+    }
 
-        if self
+    fn delete_identity(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+    ) -> Result<IdentityEvent, String> {
+        self.authenticate(authorization, private_input)?;
+        self.release(&authorization.account);
+        Ok(IdentityEvent::IdentityDeleted {
+            account: authorization.account.clone(),
+        })
+    }
+
+    /// Extends the expiry of the account, from its current expiry or from now if it is already expired.
+    fn renew_identity(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+    ) -> Result<IdentityEvent, String> {
+        let period = self
+            .expiry
+            .as_ref()
+            .ok_or("Accounts do not expire".to_string())?
+            .period;
+        let block_height = self.block_height()?;
+
+        // The owner of an expired account can still prove its credential to renew it
+        self.check_credential(authorization, private_input)?;
+        let stored_info = self
             .identities
-            .insert(account.to_string(), account_info)
-            .is_some()
-        {
-            return Err("Identity already exists".to_string());
+            .get_mut(&authorization.account)
+            .ok_or("Identity not found".to_string())?;
+        let expires_at = expires_after(
+            stored_info.expires_at.unwrap_or(0).max(block_height),
+            period,
+        )?;
+        stored_info.expires_at = Some(expires_at);
+        Ok(IdentityEvent::IdentityRenewed {
+            account: authorization.account.clone(),
+            expires_at,
+        })
+    }
+
+    fn add_attribute(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+        attestation: Attestation,
+    ) -> Result<IdentityEvent, String> {
+        attestation.verify(&authorization.account)?;
+
+        self.authenticate(authorization, private_input)?;
+        let stored_info = self
+            .identities
+            .get_mut(&authorization.account)
+            .ok_or("Identity not found".to_string())?;
+        stored_info
+            .attributes
+            .retain(|a| a.name != attestation.name || a.issuer != attestation.issuer);
+        stored_info.attributes.push(attestation.clone());
+        Ok(IdentityEvent::AttributeAdded {
+            account: authorization.account.clone(),
+            name: attestation.name,
+            issuer: attestation.issuer,
+        })
+    }
+
+    /// Checks, in the guest, that the issuer attested the opened value and that it satisfies the predicate.
+    /// Only the query is part of the output, the value stays private. The nonce of the account is consumed.
+    fn prove_attribute(
+        &mut self,
+        authorization: &Authorization,
+        attribute: AttributeQuery,
+        proof: &AttributeProof,
+    ) -> Result<IdentityEvent, String> {
+        let account = &authorization.account;
+        self.check_expiry(account)?;
+        let attestation = self
+            .identities
+            .get(account)
+            .ok_or("Identity not found".to_string())?
+            .attributes
+            .iter()
+            .find(|a| a.name == attribute.name && a.issuer == attribute.issuer)
+            .ok_or(format!(
+                "No attribute {} attested by {}",
+                attribute.name, attribute.issuer
+            ))?;
+        attestation.verify(account)?;
+        if proof.opening.commitment() != attestation.commitment {
+            return Err("Invalid attribute opening".to_string());
+        }
+        if !attribute.predicate.holds(proof.opening.value) {
+            return Err(format!("{account} does not satisfy {attribute}"));
         }
-        Ok("Successfully registered identity for account: {}".to_string())
+
+        self.authenticate(authorization, &proof.credential)?;
+        Ok(IdentityEvent::AttributeProven {
+            account: account.clone(),
+            attribute,
+        })
+    }
+
+    /// Removes an account, and drops it from the guardians of the other accounts:
+    /// whoever registers the name next does not inherit the approvals it could give.
+    /// Thresholds are lowered to the guardians left, so that the accounts can still be recovered.
+    fn release(&mut self, account: &str) {
+        self.identities.remove(account);
+        for info in self.identities.values_mut() {
+            let Some(guardians) = info.guardians.as_mut() else {
+                continue;
+            };
+            guardians.members.remove(account);
+            guardians.approvals.remove(account);
+            guardians.threshold = guardians.threshold.min(guardians.members.len() as u32);
+            if guardians.members.is_empty() {
+                info.guardians = None;
+            }
+        }
+    }
+
+    /// Height of the block including the transaction, needed by time-based checks.
+    fn block_height(&self) -> Result<u64, String> {
+        self.block_height
+            .ok_or("Missing transaction context".to_string())
+    }
+
+    /// Checks that the account has not expired.
+    fn check_expiry(&self, account: &str) -> Result<(), String> {
+        let stored_info = self
+            .identities
+            .get(account)
+            .ok_or("Identity not found".to_string())?;
+        if let Some(expires_at) = stored_info.expires_at {
+            if self.block_height()? >= expires_at {
+                return Err("Identity expired".to_string());
+            }
+        }
+        Ok(())
     }
 
     fn verify_identity(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+    ) -> Result<IdentityEvent, String> {
+        self.authenticate(authorization, private_input)?;
+        Ok(IdentityEvent::verified(authorization))
+    }
+
+    /// Checks that the authorized account has not expired, and that the private input proves its identity.
+    /// Its nonce is consumed.
+    fn authenticate(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+    ) -> Result<(), String> {
+        self.check_expiry(&authorization.account)?;
+        self.check_credential(authorization, private_input)
+    }
+
+    /// Checks that the private input proves the identity of the authorized account, and consumes its nonce.
+    fn check_credential(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+    ) -> Result<(), String> {
+        let stored_info = self
+            .identities
+            .get_mut(&authorization.account)
+            .ok_or("Identity not found".to_string())?;
+        check_nonce(stored_info, authorization.nonce)?;
+        match &stored_info.credential {
+            Credential::Password { .. } => stored_info
+                .credential
+                .check_password(password(private_input)?)?,
+            // Signing the authorization ties the verification to this transaction only
+            Credential::PublicKey(_) | Credential::Multisig { .. } => stored_info
+                .credential
+                .check_signatures(&authorization.message(), private_input)?,
+        }
+        stored_info.nonce += 1;
+        Ok(())
+    }
+
+    /// Replaces the password of `account`. Like a verification, it consumes the nonce of the account,
+    /// so that the same change cannot be replayed. Sessions created with the old password are revoked.
+    /// Changing to the same password with more iterations strengthens its hash.
+    fn change_password(
         &mut self,
         account: &str,
         nonce: u32,
-        private_input: &str,
-    ) -> Result<String, String> {
-        match self.identities.get_mut(account) {
-            Some(stored_info) => {
-                if nonce != stored_info.nonce {
-                    return Err("Invalid nonce".to_string());
-                }
-                let id = format!("{account}:{private_input}");
-                let mut hasher = Sha256::new();
-                hasher.update(id.as_bytes());
-                let hashed = hex::encode(hasher.finalize());
-                if *stored_info.hash != hashed {
+        change: &PasswordChange,
+    ) -> Result<IdentityEvent, String> {
+        let kdf = change.kdf.as_ref().unwrap_or(&self.kdf);
+        if kdf.iterations < self.kdf.iterations {
+            return Err(format!(
+                "Passwords are hashed with at least {} iterations",
+                self.kdf.iterations
+            ));
+        }
+
+        self.check_expiry(account)?;
+        let stored_info = self
+            .identities
+            .get_mut(account)
+            .ok_or("Identity not found".to_string())?;
+        check_nonce(stored_info, nonce)?;
+        stored_info
+            .credential
+            .check_password(&change.old_password)?;
+        stored_info.credential = Credential::password(&change.new_password, change.new_salt, kdf);
+        stored_info.sessions.clear();
+        stored_info.nonce += 1;
+        Ok(IdentityEvent::PasswordChanged {
+            account: account.to_string(),
+            nonce,
+        })
+    }
+
+    /// Replaces the guardians of the account. Pending approvals are dropped.
+    fn set_guardians(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+        guardians: BTreeSet<String>,
+        threshold: u32,
+    ) -> Result<IdentityEvent, String> {
+        let account = &authorization.account;
+        if threshold == 0 || threshold as usize > guardians.len() {
+            return Err("Threshold must be between 1 and the number of guardians".to_string());
+        }
+        if guardians.contains(account) {
+            return Err("An account cannot be its own guardian".to_string());
+        }
+        if let Some(guardian) = guardians.iter().find(|g| !self.identities.contains_key(*g)) {
+            return Err(format!("Guardian {guardian} not found"));
+        }
+
+        self.authenticate(authorization, private_input)?;
+        let stored_info = self
+            .identities
+            .get_mut(account)
+            .ok_or("Identity not found".to_string())?;
+        stored_info.guardians = Some(Guardians {
+            members: guardians.clone(),
+            threshold,
+            approvals: BTreeMap::new(),
+        });
+        Ok(IdentityEvent::GuardiansSet {
+            account: account.clone(),
+            guardians,
+            threshold,
+        })
+    }
+
+    /// Records the approval by a guardian of a new credential for `account`.
+    /// A guardian has a single approval, replaced if it approves another credential.
+    fn approve_recovery(
+        &mut self,
+        account: &str,
+        authorization: &Authorization,
+        private_input: &[u8],
+        credential: [u8; 32],
+    ) -> Result<IdentityEvent, String> {
+        let guardian = &authorization.account;
+        let is_guardian = self
+            .identities
+            .get(account)
+            .ok_or("Identity not found".to_string())?
+            .guardians
+            .as_ref()
+            .is_some_and(|guardians| guardians.members.contains(guardian));
+        if !is_guardian {
+            return Err(format!("{guardian} is not a guardian of {account}"));
+        }
+
+        self.authenticate(authorization, private_input)?;
+        if let Some(guardians) = self
+            .identities
+            .get_mut(account)
+            .and_then(|info| info.guardians.as_mut())
+        {
+            guardians.approvals.insert(guardian.clone(), credential);
+        }
+        Ok(IdentityEvent::RecoveryApproved {
+            account: account.to_string(),
+            guardian: guardian.clone(),
+            credential,
+        })
+    }
+
+    /// Gives `account` its new credential, once approved by enough guardians. The nonce is consumed and
+    /// the sessions are revoked, so that verifications signed by the lost credential cannot be used anymore.
+    fn complete_recovery(
+        &mut self,
+        account: &str,
+        new_credential: NewCredential,
+    ) -> Result<IdentityEvent, String> {
+        let credential = new_credential.credential(&self.kdf);
+        let digest = credential.digest();
+        let stored_info = self
+            .identities
+            .get_mut(account)
+            .ok_or("Identity not found".to_string())?;
+        let guardians = stored_info
+            .guardians
+            .as_mut()
+            .ok_or(format!("{account} has no guardians"))?;
+
+        let approvals = guardians
+            .approvals
+            .values()
+            .filter(|approved| **approved == digest)
+            .count();
+        if approvals < guardians.threshold as usize {
+            return Err(format!(
+                "Recovery approved by {approvals} guardians, {} needed",
+                guardians.threshold
+            ));
+        }
+
+        guardians.approvals.clear();
+        stored_info.credential = credential;
+        stored_info.sessions.clear();
+        stored_info.nonce += 1;
+        Ok(IdentityEvent::RecoveryCompleted {
+            account: account.to_string(),
+        })
+    }
+
+    /// Adds a session to the account, replacing any session of the same key. Expired sessions are dropped.
+    fn create_session(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+        session: Session,
+    ) -> Result<IdentityEvent, String> {
+        let block_height = self.block_height()?;
+        if session.expires_at <= block_height {
+            return Err("Session is already expired".to_string());
+        }
+        if session.contracts.is_empty() {
+            return Err("A session must be scoped to at least one contract".to_string());
+        }
+
+        self.authenticate(authorization, private_input)?;
+        let stored_info = self
+            .identities
+            .get_mut(&authorization.account)
+            .ok_or("Identity not found".to_string())?;
+        stored_info
+            .sessions
+            .retain(|s| s.expires_at > block_height && s.key != session.key);
+        stored_info.sessions.push(session.clone());
+        Ok(IdentityEvent::SessionCreated {
+            account: authorization.account.clone(),
+            key: session.key,
+            expires_at: session.expires_at,
+            contracts: session.contracts,
+        })
+    }
+
+    fn revoke_session(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+        key: PublicKey,
+    ) -> Result<IdentityEvent, String> {
+        self.authenticate(authorization, private_input)?;
+        let stored_info = self
+            .identities
+            .get_mut(&authorization.account)
+            .ok_or("Identity not found".to_string())?;
+        let sessions = stored_info.sessions.len();
+        stored_info.sessions.retain(|s| s.key != key);
+        if stored_info.sessions.len() == sessions {
+            return Err("Session not found".to_string());
+        }
+        Ok(IdentityEvent::SessionRevoked {
+            account: authorization.account.clone(),
+            key,
+        })
+    }
+
+    /// Verifies the identity of the account with a signature by one of its sessions, which must not be
+    /// expired, and whose scope must cover all the other blobs of the transaction.
+    fn verify_session(
+        &mut self,
+        authorization: &Authorization,
+        key: &PublicKey,
+        signature: &[u8],
+    ) -> Result<IdentityEvent, String> {
+        let block_height = self.block_height()?;
+        self.check_expiry(&authorization.account)?;
+        let stored_info = self
+            .identities
+            .get_mut(&authorization.account)
+            .ok_or("Identity not found".to_string())?;
+        check_nonce(stored_info, authorization.nonce)?;
+        let session = stored_info
+            .sessions
+            .iter()
+            .find(|s| s.key == *key)
+            .ok_or("Session not found".to_string())?;
+        if block_height >= session.expires_at {
+            return Err("Session expired".to_string());
+        }
+        if let Some((_, blob)) = authorization
+            .blobs
+            .iter()
+            .find(|(_, blob)| !session.contracts.contains(&blob.contract_name.0))
+        {
+            return Err(format!(
+                "Session is not allowed on contract {}",
+                blob.contract_name.0
+            ));
+        }
+        key.verify(&authorization.message(), signature)?;
+        stored_info.nonce += 1;
+        Ok(IdentityEvent::verified(authorization))
+    }
+
+    fn add_member(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+        key: PublicKey,
+    ) -> Result<IdentityEvent, String> {
+        let (members, _) = self.multisig(&authorization.account)?;
+        if members.contains(&key) {
+            return Err(format!("{key} is already a member"));
+        }
+
+        self.authenticate(authorization, private_input)?;
+        if let Some(Credential::Multisig { members, .. }) = self
+            .identities
+            .get_mut(&authorization.account)
+            .map(|info| &mut info.credential)
+        {
+            members.push(key.clone());
+        }
+        Ok(IdentityEvent::MemberAdded {
+            account: authorization.account.clone(),
+            key,
+        })
+    }
+
+    /// Removes a member, as long as enough members are left to reach the threshold.
+    fn remove_member(
+        &mut self,
+        authorization: &Authorization,
+        private_input: &[u8],
+        key: PublicKey,
+    ) -> Result<IdentityEvent, String> {
+        let (members, threshold) = self.multisig(&authorization.account)?;
+        if !members.contains(&key) {
+            return Err(format!("{key} is not a member"));
+        }
+        if members.len() <= threshold as usize {
+            return Err(format!("A multisig needs at least {threshold} members"));
+        }
+
+        self.authenticate(authorization, private_input)?;
+        if let Some(Credential::Multisig { members, .. }) = self
+            .identities
+            .get_mut(&authorization.account)
+            .map(|info| &mut info.credential)
+        {
+            members.retain(|member| *member != key);
+        }
+        Ok(IdentityEvent::MemberRemoved {
+            account: authorization.account.clone(),
+            key,
+        })
+    }
+
+    /// Members and threshold of a multisig account.
+    fn multisig(&self, account: &str) -> Result<(&[PublicKey], u32), String> {
+        match &self
+            .identities
+            .get(account)
+            .ok_or("Identity not found".to_string())?
+            .credential
+        {
+            Credential::Multisig { members, threshold } => Ok((members, *threshold)),
+            _ => Err("Account is not a multisig".to_string()),
+        }
+    }
+}
+
+/// Reads the password sent as private input.
+fn password(private_input: &[u8]) -> Result<&str, String> {
+    core::str::from_utf8(private_input).map_err(|_| "Password is not valid UTF-8".to_string())
+}
+
+/// Hash of a password, as stored in `Credential::Password`. PBKDF2 is iterated so that brute-forcing
+/// the hashes read from the on-chain state is slow, and salted so that each account has to be attacked separately.
+fn hash_password(password: &str, salt: &[u8; SALT_LEN], kdf: &KdfParams) -> String {
+    let mut hash = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, kdf.iterations, &mut hash);
+    hex::encode(hash)
+}
+
+/// Expiry of an account valid for `period` blocks from `block_height`.
+fn expires_after(block_height: u64, period: u64) -> Result<u64, String> {
+    block_height
+        .checked_add(period)
+        .ok_or("Expiry overflows the block height".to_string())
+}
+
+/// Checks that `nonce` is the next nonce of the account.
+fn check_nonce(stored_info: &AccountInfo, nonce: u32) -> Result<(), String> {
+    if nonce != stored_info.nonce {
+        return Err("Invalid nonce".to_string());
+    }
+    Ok(())
+}
+
+impl Credential {
+    pub fn password(password: &str, salt: [u8; SALT_LEN], kdf: &KdfParams) -> Self {
+        Credential::Password {
+            hash: hash_password(password, &salt, kdf),
+            salt,
+            kdf: kdf.clone(),
+        }
+    }
+
+    /// Multisig credential, checking that the threshold can be reached by distinct members.
+    pub fn multisig(members: Vec<PublicKey>, threshold: u32) -> Result<Self, String> {
+        if threshold == 0 || threshold as usize > members.len() {
+            return Err("Threshold must be between 1 and the number of members".to_string());
+        }
+        if members
+            .iter()
+            .enumerate()
+            .any(|(i, key)| members[..i].contains(key))
+        {
+            return Err("Members must be distinct".to_string());
+        }
+        Ok(Credential::Multisig { members, threshold })
+    }
+
+    /// Digest of the credential, approved by guardians to recover an account.
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(borsh::to_vec(self).expect("Failed to encode Credential")).into()
+    }
+
+    /// Checks that `password` is the password of the account.
+    fn check_password(&self, password: &str) -> Result<(), String> {
+        match self {
+            Credential::Password { hash, salt, kdf } => {
+                if *hash != hash_password(password, salt, kdf) {
                     return Err("Invalid private input".to_string());
                 }
-                stored_info.nonce += 1;
-                Ok("Identity verified".to_string())
+                Ok(())
             }
-            None => Err("Identity not found".to_string()),
+            _ => Err("Account is not protected by a password".to_string()),
+        }
+    }
+
+    /// Checks that the private input signs `message`: a signature of the key, or a `MultisigSignatures`
+    /// by at least `threshold` distinct members.
+    fn check_signatures(&self, message: &[u8], private_input: &[u8]) -> Result<(), String> {
+        match self {
+            Credential::PublicKey(key) => key.verify(message, private_input),
+            Credential::Multisig { members, threshold } => {
+                let signatures: MultisigSignatures = borsh::from_slice(private_input)
+                    .map_err(|_| "Failed to decode multisig signatures".to_string())?;
+                let mut signers: Vec<&PublicKey> = vec![];
+                for (key, signature) in &signatures.0 {
+                    if !members.contains(key) {
+                        return Err(format!("{key} is not a member"));
+                    }
+                    if signers.contains(&key) {
+                        return Err(format!("{key} signed twice"));
+                    }
+                    key.verify(message, signature)?;
+                    signers.push(key);
+                }
+                if signers.len() < *threshold as usize {
+                    return Err(format!(
+                        "Signed by {} members, {threshold} needed",
+                        signers.len()
+                    ));
+                }
+                Ok(())
+            }
+            Credential::Password { .. } => Err("Account is not protected by keys".to_string()),
+        }
+    }
+}
+
+impl NewCredential {
+    pub fn credential(&self, kdf: &KdfParams) -> Credential {
+        match self {
+            NewCredential::Password(registration) => {
+                Credential::password(&registration.password, registration.salt, kdf)
+            }
+            NewCredential::PublicKey(key) => Credential::PublicKey(key.clone()),
+        }
+    }
+}
+
+/// Secret of an account, as given to clients: its password, or its secret key as `<scheme>:<hex>`
+#[derive(Debug, Clone)]
+pub enum Secret {
+    Password(String),
+    Key(Box<SecretKey>),
+}
+
+impl std::str::FromStr for Secret {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse::<SecretKey>() {
+            Ok(key) => Secret::Key(Box::new(key)),
+            Err(_) => Secret::Password(s.to_string()),
+        })
+    }
+}
+
+impl Secret {
+    /// Private input proving the identity of an account for `authorization`.
+    pub fn prove(&self, authorization: &Authorization) -> Vec<u8> {
+        match self {
+            Secret::Password(password) => password.as_bytes().to_vec(),
+            Secret::Key(key) => key.sign(&authorization.message()),
+        }
+    }
+
+    /// Credential set by this secret. The salt is only used by passwords.
+    pub fn new_credential(&self, salt: [u8; SALT_LEN]) -> NewCredential {
+        match self {
+            Secret::Password(password) => NewCredential::Password(Registration {
+                password: password.clone(),
+                salt,
+            }),
+            Secret::Key(key) => NewCredential::PublicKey(key.public_key()),
         }
     }
 }
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{blob, execute, key, register, registration, Tx};
+use contract_identity::attributes::{Attestation, AttributeOpening, AttributeQuery, Predicate};
+use contract_identity::keys::SecretKey;
+use contract_identity::{AttributeProof, IdentityAction, IdentityContractState, IdentityEvent};
+
+const ACCOUNT: &str = "alice.simple_identity";
+
+fn issuer() -> SecretKey {
+    key(9)
+}
+
+/// Registers alice, with a birth year of 1990 attested by the issuer.
+fn setup() -> (IdentityContractState, AttributeOpening) {
+    let mut state = common::state();
+    execute(
+        &mut state,
+        ACCOUNT,
+        1,
+        register(ACCOUNT),
+        &registration("abc123"),
+    )
+    .unwrap();
+
+    let opening = AttributeOpening {
+        value: 1990,
+        salt: [3; 32],
+    };
+    let action = IdentityAction::AddAttribute {
+        account: ACCOUNT.to_string(),
+        nonce: 0,
+        attestation: Attestation::issue(&issuer(), ACCOUNT, "birth_year", &opening),
+    };
+    execute(&mut state, ACCOUNT, 1, action, b"abc123").unwrap();
+    (state, opening)
+}
+
+fn prove(predicate: Predicate) -> IdentityAction {
+    IdentityAction::ProveAttribute {
+        account: ACCOUNT.to_string(),
+        nonce: 1,
+        attribute: AttributeQuery {
+            name: "birth_year".to_string(),
+            issuer: issuer().public_key(),
+            predicate,
+        },
+    }
+}
+
+/// Private input opening the attribute, with `password` as credential.
+fn proof(opening: &AttributeOpening, password: &str) -> Vec<u8> {
+    borsh::to_vec(&AttributeProof {
+        opening: opening.clone(),
+        credential: password.as_bytes().to_vec(),
+    })
+    .unwrap()
+}
+
+#[test]
+fn attribute_predicate_is_proven_without_its_value() {
+    let (mut state, opening) = setup();
+    let private_input = proof(&opening, "abc123");
+
+    let event = execute(
+        &mut state.clone(),
+        ACCOUNT,
+        1,
+        prove(Predicate::AtMost(2007)),
+        &private_input,
+    )
+    .unwrap();
+    assert_eq!(
+        event,
+        IdentityEvent::AttributeProven {
+            account: ACCOUNT.to_string(),
+            attribute: AttributeQuery {
+                name: "birth_year".to_string(),
+                issuer: issuer().public_key(),
+                predicate: Predicate::AtMost(2007),
+            },
+        }
+    );
+
+    // A predicate that does not hold makes the blob fail
+    let err = execute(
+        &mut state,
+        ACCOUNT,
+        1,
+        prove(Predicate::AtLeast(2000)),
+        &private_input,
+    )
+    .unwrap_err();
+    assert!(err.contains("does not satisfy"));
+}
+
+#[test]
+fn attribute_cannot_be_forged() {
+    let (mut state, _) = setup();
+
+    // The value must be the one the issuer committed to
+    let forged = AttributeOpening {
+        value: 2000,
+        salt: [3; 32],
+    };
+    let err = execute(
+        &mut state.clone(),
+        ACCOUNT,
+        1,
+        prove(Predicate::AtLeast(2000)),
+        &proof(&forged, "abc123"),
+    )
+    .unwrap_err();
+    assert_eq!(err, "Invalid attribute opening");
+
+    // An attestation signed by someone else is rejected
+    let mallory = key(6);
+    let mut attestation = Attestation::issue(&mallory, ACCOUNT, "birth_year", &forged);
+    attestation.issuer = issuer().public_key();
+    let action = IdentityAction::AddAttribute {
+        account: ACCOUNT.to_string(),
+        nonce: 1,
+        attestation,
+    };
+    let err = execute(&mut state, ACCOUNT, 1, action, b"abc123").unwrap_err();
+    assert_eq!(err, "Invalid issuer signature");
+}
+
+#[test]
+fn attribute_proof_needs_the_credential_of_the_account() {
+    let (mut state, opening) = setup();
+
+    // The issuer knows the opening, but not the password: its proof cannot authorize a transfer of alice
+    let tx = Tx::new(ACCOUNT, "02", &prove(Predicate::Attested))
+        .with_blob(blob("simple_token", b"transfer 100 to mallory"));
+    let err = tx
+        .execute(&mut state, &proof(&opening, "guess"))
+        .unwrap_err();
+    assert_eq!(err, "Invalid private input");
+    assert_eq!(state.get_nonce(ACCOUNT), Ok(1));
+
+    // The account proves the attribute once per nonce
+    tx.execute(&mut state, &proof(&opening, "abc123")).unwrap();
+    let err = tx
+        .execute(&mut state, &proof(&opening, "abc123"))
+        .unwrap_err();
+    assert_eq!(err, "Invalid nonce");
+}
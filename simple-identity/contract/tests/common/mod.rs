@@ -0,0 +1,144 @@
+//! Helpers shared by the tests of the contract. Each test file uses only some of them.
+#![allow(dead_code)]
+
+use contract_identity::keys::SecretKey;
+use contract_identity::{
+    Authorization, IdentityAction, IdentityContractState, IdentityEvent, IdentityOutput, KdfParams,
+    MultisigSignatures, Registration,
+};
+use sdk::{Blob, BlobData, BlobIndex, BlockHeight, ContractInput, HyleContract, TxContext, TxHash};
+
+pub const CONTRACT_NAME: &str = "simple_identity";
+
+/// State of the contract hashing passwords with a single iteration, which keeps the tests fast.
+pub fn state() -> IdentityContractState {
+    IdentityContractState::with_kdf(KdfParams { iterations: 1 })
+}
+
+/// ed25519 key derived from `seed`.
+pub fn key(seed: u8) -> SecretKey {
+    format!("ed25519:{}", hex::encode([seed; 32]))
+        .parse()
+        .unwrap()
+}
+
+/// Blob of a contract other than the identity contract.
+pub fn blob(contract: &str, data: &[u8]) -> Blob {
+    Blob {
+        contract_name: contract.into(),
+        data: BlobData(data.to_vec()),
+    }
+}
+
+pub fn register(account: &str) -> IdentityAction {
+    IdentityAction::RegisterIdentity {
+        account: account.to_string(),
+    }
+}
+
+/// Private input registering `password`, with a fixed salt.
+pub fn registration(password: &str) -> Vec<u8> {
+    borsh::to_vec(&Registration {
+        password: password.to_string(),
+        salt: [1; 16],
+    })
+    .unwrap()
+}
+
+pub fn verify(account: &str, nonce: u32) -> IdentityAction {
+    IdentityAction::VerifyIdentity {
+        account: account.to_string(),
+        nonce,
+    }
+}
+
+/// Executes `action` alone in a transaction of `identity` included at `block_height`, whose hash is the height.
+pub fn execute(
+    state: &mut IdentityContractState,
+    identity: &str,
+    block_height: u64,
+    action: IdentityAction,
+    private_input: &[u8],
+) -> Result<IdentityEvent, String> {
+    Tx::new(identity, &format!("{block_height:x}"), &action)
+        .at(block_height)
+        .execute(state, private_input)
+}
+
+/// Transaction whose blob at index 0 is an action of the identity contract.
+pub struct Tx {
+    pub identity: String,
+    pub tx_hash: TxHash,
+    pub block_height: Option<u64>,
+    pub blobs: Vec<Blob>,
+}
+
+impl Tx {
+    pub fn new(identity: &str, tx_hash: &str, action: &IdentityAction) -> Self {
+        Tx {
+            identity: identity.to_string(),
+            tx_hash: TxHash(tx_hash.to_string()),
+            block_height: None,
+            blobs: vec![blob(CONTRACT_NAME, &borsh::to_vec(action).unwrap())],
+        }
+    }
+
+    /// Includes the transaction in the block `block_height`.
+    pub fn at(mut self, block_height: u64) -> Self {
+        self.block_height = Some(block_height);
+        self
+    }
+
+    /// Appends a blob after the identity action.
+    pub fn with_blob(mut self, blob: Blob) -> Self {
+        self.blobs.push(blob);
+        self
+    }
+
+    /// Authorization of the identity action for `account`.
+    pub fn authorization(&self, account: &str, nonce: u32) -> Authorization {
+        Authorization::new(account, nonce, &self.tx_hash, &self.blobs, BlobIndex(0))
+    }
+
+    /// Signature of the authorization of the identity action by `key`.
+    pub fn sign(&self, key: &SecretKey, account: &str, nonce: u32) -> Vec<u8> {
+        key.sign(&self.authorization(account, nonce).message())
+    }
+
+    /// Executes the identity action. A failed transaction does not change the state.
+    pub fn execute(
+        &self,
+        state: &mut IdentityContractState,
+        private_input: &[u8],
+    ) -> Result<IdentityEvent, String> {
+        let input = ContractInput {
+            state: state.as_bytes().unwrap(),
+            identity: self.identity.as_str().into(),
+            index: BlobIndex(0),
+            blobs: self.blobs.clone(),
+            tx_hash: self.tx_hash.clone(),
+            tx_ctx: self.block_height.map(|block_height| TxContext {
+                block_height: BlockHeight(block_height),
+                ..Default::default()
+            }),
+            private_input: private_input.to_vec(),
+        };
+        let mut next_state = state.clone();
+        let (output, _, _) = next_state.execute(&input)?;
+        *state = next_state;
+        Ok(IdentityOutput::decode(&output)?.event().clone())
+    }
+
+    /// Executes the identity action of the multisig account of the transaction, the private input being
+    /// the signatures by `signers` of its authorization with `nonce`.
+    pub fn execute_multisig(
+        &self,
+        state: &mut IdentityContractState,
+        signers: &[SecretKey],
+        nonce: u32,
+    ) -> Result<IdentityEvent, String> {
+        let authorization = self.authorization(&self.identity, nonce);
+        let signatures = MultisigSignatures::sign(signers, &authorization);
+        self.execute(state, &borsh::to_vec(&signatures).unwrap())
+    }
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use common::{execute, registration, verify};
+use contract_identity::{ExpiryParams, IdentityAction, IdentityContractState, IdentityEvent};
+
+const ALICE: &str = "alice.simple_identity";
+
+/// Accounts expire 100 blocks after their registration, and are released 50 blocks later.
+fn state() -> IdentityContractState {
+    common::state().with_expiry(Some(ExpiryParams {
+        period: 100,
+        grace_period: 50,
+    }))
+}
+
+fn register(
+    state: &mut IdentityContractState,
+    block_height: u64,
+    account: &str,
+    password: &str,
+) -> Result<IdentityEvent, String> {
+    execute(
+        state,
+        ALICE,
+        block_height,
+        common::register(account),
+        &registration(password),
+    )
+}
+
+#[test]
+fn expired_account_is_renewed_or_released() {
+    let mut state = state();
+    let account = ALICE;
+    register(&mut state, 10, account, "abc123").unwrap();
+
+    execute(&mut state, ALICE, 109, verify(account, 0), b"abc123").unwrap();
+    let err = execute(&mut state, ALICE, 110, verify(account, 1), b"abc123").unwrap_err();
+    assert_eq!(err, "Identity expired");
+
+    // During the grace period, the name cannot be taken but the owner can renew it
+    let err = register(&mut state, 150, account, "mallory").unwrap_err();
+    assert_eq!(err, "Identity already exists");
+    let renew = IdentityAction::RenewIdentity {
+        account: account.to_string(),
+        nonce: 1,
+    };
+    let event = execute(&mut state, ALICE, 159, renew, b"abc123").unwrap();
+    assert_eq!(
+        event,
+        IdentityEvent::IdentityRenewed {
+            account: account.to_string(),
+            expires_at: 259,
+        }
+    );
+    execute(&mut state, ALICE, 258, verify(account, 2), b"abc123").unwrap();
+
+    // Once the grace period is over, anyone can register the name
+    let err = register(&mut state, 308, account, "mallory").unwrap_err();
+    assert_eq!(err, "Identity already exists");
+    register(&mut state, 309, account, "mallory").unwrap();
+    let err = execute(&mut state, ALICE, 310, verify(account, 0), b"abc123").unwrap_err();
+    assert_eq!(err, "Invalid private input");
+}
+
+#[test]
+fn deleted_account_is_released_and_loses_its_guardianships() {
+    let mut state = state();
+    register(&mut state, 1, ALICE, "abc123").unwrap();
+    register(&mut state, 1, "carol.simple_identity", "carol").unwrap();
+    let set_guardians = IdentityAction::SetGuardians {
+        account: ALICE.to_string(),
+        nonce: 0,
+        guardians: BTreeSet::from(["carol.simple_identity".to_string()]),
+        threshold: 1,
+    };
+    execute(&mut state, ALICE, 2, set_guardians, b"abc123").unwrap();
+
+    let delete = IdentityAction::DeleteIdentity {
+        account: "carol.simple_identity".to_string(),
+        nonce: 0,
+    };
+    execute(&mut state, ALICE, 3, delete, b"carol").unwrap();
+
+    // Whoever registers the name next is not a guardian of alice
+    register(&mut state, 4, "carol.simple_identity", "mallory").unwrap();
+    let approve = IdentityAction::ApproveRecovery {
+        account: ALICE.to_string(),
+        guardian: "carol.simple_identity".to_string(),
+        nonce: 0,
+        credential: [0; 32],
+    };
+    let err = execute(&mut state, ALICE, 5, approve, b"mallory").unwrap_err();
+    assert_eq!(
+        err,
+        "carol.simple_identity is not a guardian of alice.simple_identity"
+    );
+}
+
+#[test]
+fn expiry_overflow_is_rejected() {
+    let mut state = common::state().with_expiry(Some(ExpiryParams {
+        period: u64::MAX - 10,
+        grace_period: u64::MAX,
+    }));
+    let account = ALICE;
+    register(&mut state, 10, account, "abc123").unwrap();
+
+    // The account expires at the last block, its grace period does not wrap around
+    let err = register(&mut state, u64::MAX - 1, account, "mallory").unwrap_err();
+    assert_eq!(err, "Identity already exists");
+
+    let renew = IdentityAction::RenewIdentity {
+        account: account.to_string(),
+        nonce: 0,
+    };
+    let err = execute(&mut state, ALICE, 11, renew, b"abc123").unwrap_err();
+    assert_eq!(err, "Expiry overflows the block height");
+
+    let err = register(&mut state, 11, "bob.simple_identity", "bob").unwrap_err();
+    assert_eq!(err, "Expiry overflows the block height");
+}
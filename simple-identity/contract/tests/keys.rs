@@ -0,0 +1,125 @@
+mod common;
+
+use common::{verify, Tx};
+use contract_identity::keys::{eth_message_hash, recover_eth_address, PublicKey, SecretKey};
+use contract_identity::{IdentityAction, IdentityContractState};
+
+const ACCOUNT: &str = "bob.simple_identity";
+
+/// Key of `scheme`, `secp256k1` or `ethereum`, derived from `seed`.
+fn secret_key(scheme: &str, seed: u8) -> SecretKey {
+    format!("{scheme}:{}", hex::encode([seed; 32]))
+        .parse()
+        .unwrap()
+}
+
+/// Registers bob with `key`.
+fn register(key: &SecretKey) -> IdentityContractState {
+    let mut state = common::state();
+    let action = IdentityAction::RegisterKey {
+        account: ACCOUNT.to_string(),
+        key: key.public_key(),
+    };
+    let tx = Tx::new(ACCOUNT, "01", &action);
+    tx.execute(&mut state, &tx.sign(key, ACCOUNT, 0)).unwrap();
+    state
+}
+
+#[test]
+fn secp256k1_signature_verifies_the_account() {
+    let key = secret_key("secp256k1", 7);
+    let mut state = register(&key);
+    let tx = Tx::new(ACCOUNT, "02", &verify(ACCOUNT, 0));
+
+    // A signature by another key, or of another authorization, is rejected
+    let other = tx.sign(&secret_key("secp256k1", 6), ACCOUNT, 0);
+    let err = tx.execute(&mut state, &other).unwrap_err();
+    assert_eq!(err, "Invalid signature");
+    let tampered = tx.sign(&key, ACCOUNT, 1);
+    let err = tx.execute(&mut state, &tampered).unwrap_err();
+    assert_eq!(err, "Invalid signature");
+    let err = tx.execute(&mut state, &[0; 10]).unwrap_err();
+    assert_eq!(err, "Invalid secp256k1 signature");
+
+    tx.execute(&mut state, &tx.sign(&key, ACCOUNT, 0)).unwrap();
+}
+
+#[test]
+fn secp256k1_public_key_must_be_sec1_encoded() {
+    let key = PublicKey::Secp256k1(vec![2; 10]);
+    let err = key.verify(b"message", &[0; 64]).unwrap_err();
+    assert_eq!(err, "Invalid secp256k1 public key");
+}
+
+#[test]
+fn ethereum_signature_verifies_the_account() {
+    let key = secret_key("ethereum", 7);
+    let mut state = register(&key);
+    let tx = Tx::new(ACCOUNT, "02", &verify(ACCOUNT, 0));
+
+    // The address recovered from a signature by another key is another address
+    let other = tx.sign(&secret_key("ethereum", 6), ACCOUNT, 0);
+    let err = tx.execute(&mut state, &other).unwrap_err();
+    assert_eq!(err, "Invalid signature");
+    let tampered = tx.sign(&key, ACCOUNT, 1);
+    let err = tx.execute(&mut state, &tampered).unwrap_err();
+    assert_eq!(err, "Invalid signature");
+
+    tx.execute(&mut state, &tx.sign(&key, ACCOUNT, 0)).unwrap();
+}
+
+#[test]
+fn ethereum_recovery_id_is_normalized() {
+    let key = secret_key("ethereum", 7);
+    let PublicKey::Ethereum(address) = key.public_key() else {
+        panic!("Not an Ethereum key");
+    };
+    let signature = key.sign(b"message");
+    let v = signature[64];
+    assert!(v == 27 || v == 28);
+
+    // Wallets sign with v in 27/28, some libraries in 0/1: both recover the same address
+    let mut raw = signature.clone();
+    raw[64] = v - 27;
+    assert_eq!(recover_eth_address(b"message", &signature), Ok(address));
+    assert_eq!(recover_eth_address(b"message", &raw), Ok(address));
+
+    // The other recovery id gives another key
+    raw[64] = 1 - raw[64];
+    assert_ne!(recover_eth_address(b"message", &raw), Ok(address));
+
+    raw[64] = 31;
+    assert_eq!(
+        recover_eth_address(b"message", &raw),
+        Err("Invalid recovery id".to_string())
+    );
+    assert_eq!(
+        recover_eth_address(b"message", &signature[..64]),
+        Err("Invalid Ethereum signature".to_string())
+    );
+}
+
+/// Signature made by `personal_sign`, as documented by web3.js for `accounts.sign`.
+#[test]
+fn ethereum_signature_of_a_wallet_is_recovered() {
+    let key: SecretKey =
+        "ethereum:4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap();
+    let address: PublicKey = "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        .parse()
+        .unwrap();
+    assert_eq!(key.public_key(), address);
+
+    assert_eq!(
+        hex::encode(eth_message_hash(b"Some data")),
+        "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+    );
+    let signature = hex::decode(
+        "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd\
+         6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c",
+    )
+    .unwrap();
+    address.verify(b"Some data", &signature).unwrap();
+    assert_eq!(key.sign(b"Some data"), signature);
+}
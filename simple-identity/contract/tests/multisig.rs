@@ -0,0 +1,97 @@
+mod common;
+
+use common::{key, Tx};
+use contract_identity::keys::SecretKey;
+use contract_identity::{IdentityAction, IdentityContractState, IdentityEvent};
+
+const ACCOUNT: &str = "treasury.simple_identity";
+
+/// Registers a 2-of-3 multisig account.
+fn register() -> (IdentityContractState, Vec<SecretKey>) {
+    let mut state = IdentityContractState::new();
+    let keys = vec![key(1), key(2), key(3)];
+    let action = IdentityAction::RegisterMultisig {
+        account: ACCOUNT.to_string(),
+        members: keys.iter().map(SecretKey::public_key).collect(),
+        threshold: 2,
+    };
+    Tx::new(ACCOUNT, "01", &action)
+        .execute_multisig(&mut state, &keys[..2], 0)
+        .unwrap();
+    (state, keys)
+}
+
+fn verify(nonce: u32) -> IdentityAction {
+    common::verify(ACCOUNT, nonce)
+}
+
+#[test]
+fn verification_needs_threshold_signatures() {
+    let (mut state, keys) = register();
+
+    let err = Tx::new(ACCOUNT, "02", &verify(0))
+        .execute_multisig(&mut state, &keys[..1], 0)
+        .unwrap_err();
+    assert_eq!(err, "Signed by 1 members, 2 needed");
+
+    // The same member signing twice does not count as two
+    let twice = [keys[0].clone(), keys[0].clone()];
+    let err = Tx::new(ACCOUNT, "02", &verify(0))
+        .execute_multisig(&mut state, &twice, 0)
+        .unwrap_err();
+    assert_eq!(err, format!("{} signed twice", keys[0].public_key()));
+
+    let event = Tx::new(ACCOUNT, "02", &verify(0))
+        .execute_multisig(&mut state, &keys[1..], 0)
+        .unwrap();
+    assert!(matches!(
+        event,
+        IdentityEvent::IdentityVerified { nonce: 0, .. }
+    ));
+}
+
+#[test]
+fn members_are_changed_by_threshold_signatures() {
+    let (mut state, keys) = register();
+    let remove = |nonce, key: &SecretKey| IdentityAction::RemoveMember {
+        account: ACCOUNT.to_string(),
+        nonce,
+        key: key.public_key(),
+    };
+
+    // A single member cannot remove the others
+    let err = Tx::new(ACCOUNT, "02", &remove(0, &keys[1]))
+        .execute_multisig(&mut state, &keys[..1], 0)
+        .unwrap_err();
+    assert_eq!(err, "Signed by 1 members, 2 needed");
+
+    Tx::new(ACCOUNT, "02", &remove(0, &keys[2]))
+        .execute_multisig(&mut state, &keys[..2], 0)
+        .unwrap();
+
+    // The removed member does not count anymore
+    let signers = [keys[0].clone(), keys[2].clone()];
+    let err = Tx::new(ACCOUNT, "03", &verify(1))
+        .execute_multisig(&mut state, &signers, 1)
+        .unwrap_err();
+    assert_eq!(err, format!("{} is not a member", keys[2].public_key()));
+
+    // Removing another member would make the threshold unreachable
+    let err = Tx::new(ACCOUNT, "03", &remove(1, &keys[1]))
+        .execute_multisig(&mut state, &keys[..2], 1)
+        .unwrap_err();
+    assert_eq!(err, "A multisig needs at least 2 members");
+
+    let add = IdentityAction::AddMember {
+        account: ACCOUNT.to_string(),
+        nonce: 1,
+        key: key(4).public_key(),
+    };
+    Tx::new(ACCOUNT, "03", &add)
+        .execute_multisig(&mut state, &keys[..2], 1)
+        .unwrap();
+    let signers = [keys[0].clone(), key(4)];
+    Tx::new(ACCOUNT, "04", &verify(2))
+        .execute_multisig(&mut state, &signers, 2)
+        .unwrap();
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::{execute, key, register, registration, state, verify, Tx};
+use contract_identity::{
+    Credential, IdentityAction, IdentityContractState, IdentityEvent, KdfParams, PasswordChange,
+};
+
+const ACCOUNT: &str = "alice.simple_identity";
+
+/// Registers alice with the password `abc123`.
+fn setup() -> IdentityContractState {
+    let mut state = state();
+    execute(
+        &mut state,
+        ACCOUNT,
+        1,
+        register(ACCOUNT),
+        &registration("abc123"),
+    )
+    .unwrap();
+    state
+}
+
+/// Private input changing `old_password` to `new_password`, hashed with `kdf`.
+fn change(old_password: &str, new_password: &str, kdf: Option<KdfParams>) -> Vec<u8> {
+    borsh::to_vec(&PasswordChange {
+        old_password: old_password.to_string(),
+        new_password: new_password.to_string(),
+        new_salt: [2; 16],
+        kdf,
+    })
+    .unwrap()
+}
+
+fn change_password(
+    state: &mut IdentityContractState,
+    nonce: u32,
+    private_input: &[u8],
+) -> Result<IdentityEvent, String> {
+    let action = IdentityAction::ChangePassword {
+        account: ACCOUNT.to_string(),
+        nonce,
+    };
+    execute(state, ACCOUNT, 2, action, private_input)
+}
+
+#[test]
+fn password_change_needs_the_old_password() {
+    let mut state = setup();
+
+    let err = change_password(&mut state, 0, &change("guess", "def456", None)).unwrap_err();
+    assert_eq!(err, "Invalid private input");
+    assert_eq!(state.get_nonce(ACCOUNT), Ok(0));
+
+    // The password was not changed
+    execute(&mut state, ACCOUNT, 3, verify(ACCOUNT, 0), b"abc123").unwrap();
+}
+
+#[test]
+fn password_change_replaces_the_password() {
+    let mut state = setup();
+
+    let event = change_password(&mut state, 0, &change("abc123", "def456", None)).unwrap();
+    assert_eq!(
+        event,
+        IdentityEvent::PasswordChanged {
+            account: ACCOUNT.to_string(),
+            nonce: 0,
+        }
+    );
+    assert_eq!(state.get_nonce(ACCOUNT), Ok(1));
+
+    // The change consumed the nonce, it cannot be replayed
+    let err = change_password(&mut state, 0, &change("abc123", "def456", None)).unwrap_err();
+    assert_eq!(err, "Invalid nonce");
+
+    let err = execute(&mut state, ACCOUNT, 3, verify(ACCOUNT, 1), b"abc123").unwrap_err();
+    assert_eq!(err, "Invalid private input");
+    execute(&mut state, ACCOUNT, 3, verify(ACCOUNT, 1), b"def456").unwrap();
+}
+
+#[test]
+fn key_account_has_no_password_to_change() {
+    let mut state = state();
+    let key = key(7);
+    let action = IdentityAction::RegisterKey {
+        account: ACCOUNT.to_string(),
+        key: key.public_key(),
+    };
+    let tx = Tx::new(ACCOUNT, "01", &action);
+    tx.execute(&mut state, &tx.sign(&key, ACCOUNT, 0)).unwrap();
+
+    let err = change_password(&mut state, 0, &change("", "def456", None)).unwrap_err();
+    assert_eq!(err, "Account is not protected by a password");
+}
+
+#[test]
+fn salt_and_iterations_change_the_hash() {
+    let kdf = KdfParams { iterations: 2 };
+    let hash = Credential::password("abc123", [1; 16], &kdf);
+    assert_eq!(hash, Credential::password("abc123", [1; 16], &kdf));
+
+    // Accounts with the same password have different hashes, to be brute-forced separately
+    assert_ne!(hash, Credential::password("abc123", [2; 16], &kdf));
+    assert_ne!(
+        hash,
+        Credential::password("abc123", [1; 16], &KdfParams { iterations: 3 })
+    );
+}
+
+#[test]
+fn password_is_checked_with_its_own_iterations() {
+    let mut state = setup();
+
+    // The owner strengthens the hash of its password, keeping the same password
+    let private_input = change("abc123", "abc123", Some(KdfParams { iterations: 3 }));
+    change_password(&mut state, 0, &private_input).unwrap();
+    assert_eq!(state.kdf(), &KdfParams { iterations: 1 });
+
+    let err = execute(&mut state, ACCOUNT, 3, verify(ACCOUNT, 1), b"abc124").unwrap_err();
+    assert_eq!(err, "Invalid private input");
+    execute(&mut state, ACCOUNT, 3, verify(ACCOUNT, 1), b"abc123").unwrap();
+}
+
+#[test]
+fn password_hash_cannot_be_weakened() {
+    let mut state = IdentityContractState::with_kdf(KdfParams { iterations: 2 });
+    execute(
+        &mut state,
+        ACCOUNT,
+        1,
+        register(ACCOUNT),
+        &registration("abc123"),
+    )
+    .unwrap();
+
+    let private_input = change("abc123", "def456", Some(KdfParams { iterations: 1 }));
+    let err = change_password(&mut state, 0, &private_input).unwrap_err();
+    assert_eq!(err, "Passwords are hashed with at least 2 iterations");
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use common::{execute, key, register, registration, state, verify, Tx};
+use contract_identity::{
+    IdentityAction, IdentityContractState, IdentityEvent, KdfParams, NewCredential, Secret,
+};
+
+const ACCOUNT: &str = "alice.simple_identity";
+const GUARDIANS: [&str; 3] = [
+    "bob.simple_identity",
+    "carol.simple_identity",
+    "dave.simple_identity",
+];
+
+/// Registers alice and her guardians, each with its name as password, and sets 2 of them as guardians.
+/// Alice also creates a session.
+fn setup() -> IdentityContractState {
+    let mut state = state();
+    for account in [ACCOUNT].iter().chain(&GUARDIANS) {
+        execute(
+            &mut state,
+            ACCOUNT,
+            1,
+            register(account),
+            &registration(account),
+        )
+        .unwrap();
+    }
+    let set_guardians = IdentityAction::SetGuardians {
+        account: ACCOUNT.to_string(),
+        nonce: 0,
+        guardians: GUARDIANS[..2].iter().map(|g| g.to_string()).collect(),
+        threshold: 2,
+    };
+    execute(&mut state, ACCOUNT, 2, set_guardians, ACCOUNT.as_bytes()).unwrap();
+    let session = IdentityAction::CreateSession {
+        account: ACCOUNT.to_string(),
+        nonce: 1,
+        key: key(7).public_key(),
+        expires_at: 100,
+        contracts: BTreeSet::from(["ticket_app".to_string()]),
+    };
+    execute(&mut state, ACCOUNT, 3, session, ACCOUNT.as_bytes()).unwrap();
+    state
+}
+
+/// Credential given to alice by the recovery: a new key.
+fn new_credential() -> NewCredential {
+    Secret::Key(Box::new(key(8))).new_credential([0; 16])
+}
+
+fn digest(credential: &NewCredential) -> [u8; 32] {
+    credential.credential(&KdfParams { iterations: 1 }).digest()
+}
+
+/// Approval by `guardian`, proving its identity with its password.
+fn approve(
+    state: &mut IdentityContractState,
+    guardian: &str,
+    credential: [u8; 32],
+) -> Result<IdentityEvent, String> {
+    let action = IdentityAction::ApproveRecovery {
+        account: ACCOUNT.to_string(),
+        guardian: guardian.to_string(),
+        nonce: state.get_nonce(guardian).unwrap(),
+        credential,
+    };
+    execute(state, ACCOUNT, 10, action, guardian.as_bytes())
+}
+
+fn complete(
+    state: &mut IdentityContractState,
+    credential: &NewCredential,
+) -> Result<IdentityEvent, String> {
+    let action = IdentityAction::CompleteRecovery {
+        account: ACCOUNT.to_string(),
+    };
+    execute(
+        state,
+        ACCOUNT,
+        11,
+        action,
+        &borsh::to_vec(credential).unwrap(),
+    )
+}
+
+#[test]
+fn recovery_below_threshold_is_rejected() {
+    let mut state = setup();
+    let credential = new_credential();
+
+    approve(&mut state, GUARDIANS[0], digest(&credential)).unwrap();
+    let err = complete(&mut state, &credential).unwrap_err();
+    assert_eq!(err, "Recovery approved by 1 guardians, 2 needed");
+}
+
+#[test]
+fn recovery_of_another_credential_is_rejected() {
+    let mut state = setup();
+    let credential = new_credential();
+
+    // The guardians approved a credential, someone completes the recovery with another one
+    approve(&mut state, GUARDIANS[0], digest(&credential)).unwrap();
+    approve(&mut state, GUARDIANS[1], digest(&credential)).unwrap();
+    let other = Secret::Key(Box::new(key(6))).new_credential([0; 16]);
+    let err = complete(&mut state, &other).unwrap_err();
+    assert_eq!(err, "Recovery approved by 0 guardians, 2 needed");
+
+    // Approvals of different credentials do not add up
+    let mut state = setup();
+    approve(&mut state, GUARDIANS[0], digest(&credential)).unwrap();
+    approve(&mut state, GUARDIANS[1], digest(&other)).unwrap();
+    let err = complete(&mut state, &credential).unwrap_err();
+    assert_eq!(err, "Recovery approved by 1 guardians, 2 needed");
+}
+
+#[test]
+fn approval_by_a_non_guardian_is_rejected() {
+    let mut state = setup();
+    let credential = new_credential();
+
+    let err = approve(&mut state, GUARDIANS[2], digest(&credential)).unwrap_err();
+    assert_eq!(
+        err,
+        format!("{} is not a guardian of {ACCOUNT}", GUARDIANS[2])
+    );
+
+    // Nor does a guardian approve without proving its identity
+    let action = IdentityAction::ApproveRecovery {
+        account: ACCOUNT.to_string(),
+        guardian: GUARDIANS[0].to_string(),
+        nonce: 0,
+        credential: digest(&credential),
+    };
+    let err = execute(&mut state, ACCOUNT, 10, action, b"mallory").unwrap_err();
+    assert_eq!(err, "Invalid private input");
+}
+
+#[test]
+fn completed_recovery_clears_approvals_and_sessions() {
+    let mut state = setup();
+    let credential = new_credential();
+    approve(&mut state, GUARDIANS[0], digest(&credential)).unwrap();
+    approve(&mut state, GUARDIANS[1], digest(&credential)).unwrap();
+
+    let event = complete(&mut state, &credential).unwrap();
+    assert_eq!(
+        event,
+        IdentityEvent::RecoveryCompleted {
+            account: ACCOUNT.to_string(),
+        }
+    );
+    assert_eq!(state.get_nonce(ACCOUNT), Ok(3));
+
+    // The approvals are consumed, the recovery cannot be completed again
+    let err = complete(&mut state, &credential).unwrap_err();
+    assert_eq!(err, "Recovery approved by 0 guardians, 2 needed");
+
+    // The session created with the lost password is revoked
+    let action = IdentityAction::VerifySession {
+        account: ACCOUNT.to_string(),
+        nonce: 3,
+        key: key(7).public_key(),
+    };
+    let tx = Tx::new(ACCOUNT, "0c", &action).at(12);
+    let err = tx
+        .execute(&mut state, &tx.sign(&key(7), ACCOUNT, 3))
+        .unwrap_err();
+    assert_eq!(err, "Session not found");
+
+    // The lost password does not verify anymore, the new key does
+    let err = execute(
+        &mut state,
+        ACCOUNT,
+        12,
+        verify(ACCOUNT, 3),
+        ACCOUNT.as_bytes(),
+    )
+    .unwrap_err();
+    assert_eq!(err, "Invalid ed25519 signature");
+    let tx = Tx::new(ACCOUNT, "0c", &verify(ACCOUNT, 3)).at(12);
+    tx.execute(&mut state, &tx.sign(&key(8), ACCOUNT, 3))
+        .unwrap();
+}
+
+#[test]
+fn released_guardian_lowers_the_threshold() {
+    let mut state = setup();
+    let credential = new_credential();
+    let delete = |guardian: &str, nonce| IdentityAction::DeleteIdentity {
+        account: guardian.to_string(),
+        nonce,
+    };
+
+    // Carol deletes her account: bob alone is left to reach the threshold
+    execute(
+        &mut state,
+        ACCOUNT,
+        4,
+        delete(GUARDIANS[1], 0),
+        GUARDIANS[1].as_bytes(),
+    )
+    .unwrap();
+    approve(&mut state, GUARDIANS[0], digest(&credential)).unwrap();
+    complete(&mut state, &credential).unwrap();
+
+    // Without guardians left, the account cannot be recovered anymore
+    execute(
+        &mut state,
+        ACCOUNT,
+        12,
+        delete(GUARDIANS[0], 1),
+        GUARDIANS[0].as_bytes(),
+    )
+    .unwrap();
+    let err = complete(&mut state, &credential).unwrap_err();
+    assert_eq!(err, format!("{ACCOUNT} has no guardians"));
+}
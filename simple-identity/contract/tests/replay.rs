@@ -0,0 +1,101 @@
+mod common;
+
+use common::{blob, key, register, registration, state, verify, Tx};
+use contract_identity::keys::SecretKey;
+use contract_identity::{IdentityAction, IdentityContractState, IdentityEvent};
+use sdk::TxHash;
+
+/// Transaction `tx_hash` of `account` running `action`, then paying `recipient` on another contract.
+fn pay(account: &str, tx_hash: &str, action: &IdentityAction, recipient: &str) -> Tx {
+    Tx::new(account, tx_hash, action).with_blob(blob("simple_token", recipient.as_bytes()))
+}
+
+/// Registers `bob.simple_identity` with an ed25519 key.
+fn register_key(state: &mut IdentityContractState) -> SecretKey {
+    let account = "bob.simple_identity";
+    let key = key(7);
+    let action = IdentityAction::RegisterKey {
+        account: account.to_string(),
+        key: key.public_key(),
+    };
+    let tx = pay(account, "01", &action, "");
+    tx.execute(state, &tx.sign(&key, account, 0)).unwrap();
+    key
+}
+
+#[test]
+fn signature_cannot_be_replayed_in_another_transaction() {
+    let mut state = IdentityContractState::new();
+    let key = register_key(&mut state);
+    let account = "bob.simple_identity";
+    let action = verify(account, 0);
+
+    // Bob signs a verification paying alice, which is seen before being settled
+    let bob_tx = pay(account, "02", &action, "alice.simple_identity");
+    let signature = bob_tx.sign(&key, account, 0);
+
+    // Knowing the nonce and the signature, mallory tries to use them in her own transaction
+    let mallory_tx = pay(account, "03", &action, "mallory.simple_identity");
+    let err = mallory_tx.execute(&mut state, &signature).unwrap_err();
+    assert_eq!(err, "Invalid signature");
+
+    // The signature is only valid in the transaction of bob
+    let event = bob_tx.execute(&mut state, &signature).unwrap();
+    assert!(matches!(
+        event,
+        IdentityEvent::IdentityVerified { nonce: 0, .. }
+    ));
+}
+
+#[test]
+fn signature_is_bound_to_sibling_blobs() {
+    let mut state = IdentityContractState::new();
+    let key = register_key(&mut state);
+    let account = "bob.simple_identity";
+    let action = verify(account, 0);
+
+    let signature = pay(account, "02", &action, "alice.simple_identity").sign(&key, account, 0);
+
+    // Even with the same transaction hash, the signature does not authorize other blobs
+    let err = pay(account, "02", &action, "mallory.simple_identity")
+        .execute(&mut state, &signature)
+        .unwrap_err();
+    assert_eq!(err, "Invalid signature");
+}
+
+/// A password cannot sign anything: a verification is bound to its transaction only by its
+/// program output, which commits to the transaction hash and authorization it was proven for.
+#[test]
+fn password_verification_cannot_be_replayed() {
+    let mut state = state();
+    let account = "alice.simple_identity";
+    pay(account, "01", &register(account), "")
+        .execute(&mut state, &registration("abc123"))
+        .unwrap();
+
+    let action = verify(account, 0);
+    let tx = pay(account, "02", &action, "bob.simple_identity");
+    let event = tx.execute(&mut state.clone(), b"abc123").unwrap();
+
+    // The event commits to the transaction and the blobs it authorizes
+    assert_eq!(
+        event,
+        IdentityEvent::IdentityVerified {
+            account: account.to_string(),
+            nonce: 0,
+            tx_hash: TxHash("02".to_string()),
+            authorization: tx.authorization(account, 0).message(),
+        }
+    );
+
+    // The same verification in another transaction has another output, so the proof of the
+    // first one cannot settle it
+    let other_tx = pay(account, "03", &action, "mallory.simple_identity");
+    let other_event = other_tx.execute(&mut state.clone(), b"abc123").unwrap();
+    assert_ne!(other_event, event);
+
+    // Once settled, the nonce is consumed: the verification cannot be proven again in another transaction
+    tx.execute(&mut state, b"abc123").unwrap();
+    let err = other_tx.execute(&mut state, b"abc123").unwrap_err();
+    assert_eq!(err, "Invalid nonce");
+}
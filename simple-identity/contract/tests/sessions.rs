@@ -0,0 +1,96 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use common::{blob, key, register, registration, state, Tx};
+use contract_identity::keys::SecretKey;
+use contract_identity::{IdentityAction, IdentityContractState, IdentityEvent, PasswordChange};
+
+const ACCOUNT: &str = "alice.simple_identity";
+
+/// Transaction of alice included at `block_height`, running `action` followed by a blob on `contract`.
+fn tx(block_height: u64, action: &IdentityAction, contract: &str) -> Tx {
+    Tx::new(ACCOUNT, &format!("{block_height:x}"), action)
+        .at(block_height)
+        .with_blob(blob(contract, &[]))
+}
+
+/// Registers alice with the password `abc123`, then creates a session on `ticket_app` until block 100.
+fn setup() -> (IdentityContractState, SecretKey) {
+    let mut state = state();
+    tx(1, &register(ACCOUNT), "")
+        .execute(&mut state, &registration("abc123"))
+        .unwrap();
+
+    let session = key(7);
+    let action = IdentityAction::CreateSession {
+        account: ACCOUNT.to_string(),
+        nonce: 0,
+        key: session.public_key(),
+        expires_at: 100,
+        contracts: BTreeSet::from(["ticket_app".to_string()]),
+    };
+    tx(2, &action, "").execute(&mut state, b"abc123").unwrap();
+    (state, session)
+}
+
+/// Verifies alice with the session, in a transaction with a blob on `contract`.
+fn verify_session(
+    state: &mut IdentityContractState,
+    session: &SecretKey,
+    nonce: u32,
+    block_height: u64,
+    contract: &str,
+) -> Result<IdentityEvent, String> {
+    let action = IdentityAction::VerifySession {
+        account: ACCOUNT.to_string(),
+        nonce,
+        key: session.public_key(),
+    };
+    let tx = tx(block_height, &action, contract);
+    tx.execute(state, &tx.sign(session, ACCOUNT, nonce))
+}
+
+#[test]
+fn session_verifies_until_its_expiry() {
+    let (mut state, session) = setup();
+
+    let event = verify_session(&mut state, &session, 1, 99, "ticket_app").unwrap();
+    assert!(matches!(
+        event,
+        IdentityEvent::IdentityVerified { nonce: 1, .. }
+    ));
+
+    let err = verify_session(&mut state, &session, 2, 100, "ticket_app").unwrap_err();
+    assert_eq!(err, "Session expired");
+}
+
+#[test]
+fn session_is_limited_to_its_scope() {
+    let (mut state, session) = setup();
+
+    let err = verify_session(&mut state, &session, 1, 10, "simple_token").unwrap_err();
+    assert_eq!(err, "Session is not allowed on contract simple_token");
+}
+
+#[test]
+fn password_change_revokes_sessions() {
+    let (mut state, session) = setup();
+
+    let action = IdentityAction::ChangePassword {
+        account: ACCOUNT.to_string(),
+        nonce: 1,
+    };
+    let change = PasswordChange {
+        old_password: "abc123".to_string(),
+        new_password: "def456".to_string(),
+        new_salt: [2; 16],
+        kdf: None,
+    };
+    tx(10, &action, "")
+        .execute(&mut state, &borsh::to_vec(&change).unwrap())
+        .unwrap();
+
+    let err = verify_session(&mut state, &session, 2, 11, "ticket_app").unwrap_err();
+    assert_eq!(err, "Session not found");
+}
@@ -1,7 +1,8 @@
 use anyhow::Context;
 use clap::{Parser, Subcommand};
 use client_sdk::helpers::sp1::SP1Prover;
-use contract::{SimpleToken, SimpleTokenAction};
+use client_sdk::rest_client::NodeApiHttpClient;
+use contract::{AdminAction, SimpleToken, SimpleTokenAction, SimpleTokenOutput, TokenMetadata};
 use sdk::BlobTransaction;
 use sdk::ContractAction;
 use sdk::ProofTransaction;
@@ -29,16 +30,104 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Register {
-        supply: u128,
+        supply: String,
+        /// Identity allowed to mint new tokens. Defaults to the faucet account.
+        #[arg(long)]
+        minter: Option<String>,
+        /// Identity allowed to use the admin controls. Defaults to the faucet account.
+        #[arg(long)]
+        admin: Option<String>,
+        /// Maximum total supply. Unlimited if not set.
+        #[arg(long)]
+        max_supply: Option<String>,
+        #[arg(long, default_value = "Simple Token")]
+        name: String,
+        #[arg(long, default_value = "SIMPLE")]
+        symbol: String,
+        /// Number of decimals used to display amounts: with 2 decimals, `12.5` is 1250 base units
+        #[arg(long, default_value = "0")]
+        decimals: u8,
     },
     Transfer {
         from: String,
         to: String,
-        amount: u128,
+        amount: String,
     },
     Balance {
         of: String,
     },
+    /// Prints the name, symbol and decimals of the token
+    Metadata {},
+    Approve {
+        owner: String,
+        spender: String,
+        amount: String,
+    },
+    TransferFrom {
+        spender: String,
+        owner: String,
+        to: String,
+        amount: String,
+    },
+    Allowance {
+        owner: String,
+        spender: String,
+    },
+    Mint {
+        minter: String,
+        to: String,
+        amount: String,
+    },
+    /// Burns tokens of the minter
+    Burn {
+        minter: String,
+        amount: String,
+    },
+    /// Transfers tokens of the admin that are released linearly over `duration` blocks, after a `cliff`
+    CreateVesting {
+        admin: String,
+        beneficiary: String,
+        amount: String,
+        /// Number of blocks before any token is released
+        #[arg(long, default_value = "0")]
+        cliff: u64,
+        /// Number of blocks after which all tokens are released
+        duration: u64,
+    },
+    /// Prints the vesting schedule of an account, and how much is still locked
+    Vesting {
+        of: String,
+    },
+    /// Admin controls, sent on behalf of the `admin` identity
+    Admin {
+        admin: String,
+
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum AdminCommands {
+    /// Blocks all transfers
+    Pause,
+    Unpause,
+    /// Prevents an account from sending or receiving tokens
+    Freeze {
+        account: String,
+    },
+    Unfreeze {
+        account: String,
+    },
+    /// Only lets accounts in the allow-list send or receive tokens
+    EnableAllowList,
+    DisableAllowList,
+    Allow {
+        account: String,
+    },
+    Disallow {
+        account: String,
+    },
 }
 
 #[tokio::main]
@@ -50,7 +139,7 @@ async fn main() -> anyhow::Result<()> {
 
     let cli = Cli::parse();
 
-    let client = client_sdk::rest_client::NodeApiHttpClient::new(cli.host)?;
+    let client = NodeApiHttpClient::new(cli.host)?;
 
     let contract_name = &cli.contract_name;
 
@@ -58,10 +147,35 @@ async fn main() -> anyhow::Result<()> {
     let prover = SP1Prover::new(CONTRACT_ELF);
 
     match cli.command {
-        Commands::Register { supply } => {
+        Commands::Register {
+            supply,
+            minter,
+            admin,
+            max_supply,
+            name,
+            symbol,
+            decimals,
+        } => {
             // Build initial state of contract
-            let initial_state = SimpleToken::new(supply, format!("faucet.{}", contract_name));
+            let metadata = TokenMetadata {
+                name,
+                symbol,
+                decimals,
+            };
+            let supply = parse_amount(&metadata, &supply)?;
+            let max_supply = max_supply
+                .map(|max_supply| parse_amount(&metadata, &max_supply))
+                .transpose()?;
+            let faucet = format!("faucet.{}", contract_name);
+            let minter = minter.unwrap_or_else(|| faucet.clone());
+            let admin = admin.unwrap_or_else(|| faucet.clone());
+            let initial_state =
+                SimpleToken::new(supply, faucet, minter, max_supply, metadata, admin);
             println!("Initial state: {:?}", initial_state);
+            initial_state
+                .check_supply()
+                .map_err(|e| anyhow::anyhow!(e))
+                .context("invalid initial supply")?;
 
             let vk = serde_json::to_vec(&prover.vk).unwrap();
 
@@ -79,80 +193,270 @@ async fn main() -> anyhow::Result<()> {
         }
         Commands::Balance { of } => {
             // Fetch the state from the node
-            let state: SimpleToken = client
-                .get_contract(&contract_name.clone().into())
-                .await
-                .context("failed to get contract")?
-                .state
-                .into();
+            let state = fetch_state(&client, contract_name).await?;
 
             let balance = state
                 .balance_of(&of)
                 .map_err(|e| anyhow::anyhow!(e))
                 .context("failed to fetch balance")?;
-            println!("Balance of {}: {}", of, balance);
+            println!(
+                "Balance of {}: {} {}",
+                of,
+                state.metadata.format_amount(balance),
+                state.metadata.symbol
+            );
+        }
+        Commands::Metadata {} => {
+            let state = fetch_state(&client, contract_name).await?;
+            let metadata = state.metadata();
+            println!("Name: {}", metadata.name);
+            println!("Symbol: {}", metadata.symbol);
+            println!("Decimals: {}", metadata.decimals);
+            println!(
+                "Total supply: {} {}",
+                metadata.format_amount(state.total_supply),
+                metadata.symbol
+            );
         }
         Commands::Transfer { from, to, amount } => {
-            // Fetch the initial state from the node
-            let initial_state: SimpleToken = client
-                .get_contract(&contract_name.clone().into())
-                .await
-                .context("failed to get contract")?
-                .state
-                .into();
-            // ----
-            // Build the blob transaction
-            // ----
-
+            let state = fetch_state(&client, contract_name).await?;
             let action = SimpleTokenAction::Transfer {
-                recipient: to.clone(),
-                amount,
+                recipient: to,
+                amount: parse_amount(&state.metadata, &amount)?,
             };
-            println!("Action: {:#?}", action);
-            let blobs = vec![action.as_blob(contract_name.clone().into(), None, None)];
-            let blob_tx = BlobTransaction::new(from.clone(), blobs.clone());
-
-            println!("blob_tx: {:#?}", blob_tx);
-            // Send the blob transaction
-            let blob_tx_hash = client
-                .send_tx_blob(&blob_tx)
-                .await
-                .context("cannot send tx")?;
-            println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
-
-            // ----
-            // Prove the state transition
-            // ----
-
-            // Build the contract input
-            let inputs = ContractInput {
-                state: initial_state.as_bytes()?,
-                identity: from.clone().into(),
-                tx_hash: blob_tx_hash,
-                private_input: vec![],
-                tx_ctx: None,
-                blobs: blobs.clone(),
-                index: sdk::BlobIndex(0),
+            send_action(&client, &prover, contract_name, state, from, action).await?;
+        }
+        Commands::Approve {
+            owner,
+            spender,
+            amount,
+        } => {
+            let state = fetch_state(&client, contract_name).await?;
+            let action = SimpleTokenAction::Approve {
+                spender,
+                amount: parse_amount(&state.metadata, &amount)?,
             };
+            send_action(&client, &prover, contract_name, state, owner, action).await?;
+        }
+        Commands::TransferFrom {
+            spender,
+            owner,
+            to,
+            amount,
+        } => {
+            let state = fetch_state(&client, contract_name).await?;
+            let action = SimpleTokenAction::TransferFrom {
+                owner,
+                recipient: to,
+                amount: parse_amount(&state.metadata, &amount)?,
+            };
+            send_action(&client, &prover, contract_name, state, spender, action).await?;
+        }
+        Commands::Mint { minter, to, amount } => {
+            let state = fetch_state(&client, contract_name).await?;
+            let action = SimpleTokenAction::Mint {
+                recipient: to,
+                amount: parse_amount(&state.metadata, &amount)?,
+            };
+            send_action(&client, &prover, contract_name, state, minter, action).await?;
+        }
+        Commands::Burn { minter, amount } => {
+            let state = fetch_state(&client, contract_name).await?;
+            let action = SimpleTokenAction::Burn {
+                amount: parse_amount(&state.metadata, &amount)?,
+            };
+            send_action(&client, &prover, contract_name, state, minter, action).await?;
+        }
+        Commands::CreateVesting {
+            admin,
+            beneficiary,
+            amount,
+            cliff,
+            duration,
+        } => {
+            let state = fetch_state(&client, contract_name).await?;
+            let action = SimpleTokenAction::CreateVesting {
+                beneficiary,
+                amount: parse_amount(&state.metadata, &amount)?,
+                cliff,
+                duration,
+            };
+            send_action(&client, &prover, contract_name, state, admin, action).await?;
+        }
+        Commands::Vesting { of } => {
+            let mut state = fetch_state(&client, contract_name).await?;
+            let Some(schedule) = state.vesting.get(&of).cloned() else {
+                println!("No vesting schedule for {}", of);
+                return Ok(());
+            };
+            let block_height = client.get_block_height().await?.0;
+            state.block_height = Some(block_height);
 
-            println!("inputs: {:#?}", inputs);
-
-            // Generate the zk proof
-            println!("🔍 Proving state transition...");
-            let proof = prover.prove(inputs).await.unwrap();
-
-            let proof_tx = ProofTransaction {
-                proof,
-                contract_name: contract_name.clone().into(),
+            let locked = state
+                .locked_balance(&of)
+                .map_err(|e| anyhow::anyhow!(e))
+                .context("failed to compute locked balance")?;
+            let format = |amount| state.metadata.format_amount(amount);
+            println!(
+                "Vesting of {}: {} {} from block {}, cliff {} blocks, duration {} blocks",
+                of,
+                format(schedule.total),
+                state.metadata.symbol,
+                schedule.start,
+                schedule.cliff,
+                schedule.duration
+            );
+            println!(
+                "Locked at block {}: {} {}",
+                block_height,
+                format(locked),
+                state.metadata.symbol
+            );
+        }
+        Commands::Admin { admin, command } => {
+            let state = fetch_state(&client, contract_name).await?;
+            let action = match command {
+                AdminCommands::Pause => AdminAction::Pause,
+                AdminCommands::Unpause => AdminAction::Unpause,
+                AdminCommands::Freeze { account } => AdminAction::Freeze { account },
+                AdminCommands::Unfreeze { account } => AdminAction::Unfreeze { account },
+                AdminCommands::EnableAllowList => AdminAction::EnableAllowList,
+                AdminCommands::DisableAllowList => AdminAction::DisableAllowList,
+                AdminCommands::Allow { account } => AdminAction::Allow { account },
+                AdminCommands::Disallow { account } => AdminAction::Disallow { account },
             };
+            let action = SimpleTokenAction::Admin(action);
+            send_action(&client, &prover, contract_name, state, admin, action).await?;
+        }
+        Commands::Allowance { owner, spender } => {
+            // Fetch the state from the node
+            let state = fetch_state(&client, contract_name).await?;
 
-            // Send the proof transaction
-            let proof_tx_hash = client
-                .send_tx_proof(&proof_tx)
-                .await
-                .context("failed to send proof")?;
-            println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+            let allowance = state.allowance(&owner, &spender);
+            println!(
+                "Allowance of {} over {}: {} {}",
+                spender,
+                owner,
+                state.metadata.format_amount(allowance),
+                state.metadata.symbol
+            );
         }
     };
     Ok(())
 }
+
+/// Fetches the current state of the contract from the node.
+async fn fetch_state(
+    client: &NodeApiHttpClient,
+    contract_name: &str,
+) -> anyhow::Result<SimpleToken> {
+    Ok(client
+        .get_contract(&contract_name.into())
+        .await
+        .context("failed to get contract")?
+        .state
+        .into())
+}
+
+/// Parses a decimal amount such as `12.5` into base units, according to the token decimals.
+fn parse_amount(metadata: &TokenMetadata, amount: &str) -> anyhow::Result<u128> {
+    metadata
+        .parse_amount(amount)
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Sends a blob transaction for `action` on behalf of `identity`, then proves and sends its proof.
+/// `initial_state` is the current state of the contract, as fetched from the node.
+async fn send_action(
+    client: &NodeApiHttpClient,
+    prover: &SP1Prover,
+    contract_name: &str,
+    initial_state: SimpleToken,
+    identity: String,
+    action: SimpleTokenAction,
+) -> anyhow::Result<()> {
+    // ----
+    // Build the blob transaction
+    // ----
+
+    println!("Action: {:#?}", action);
+    let blobs = vec![action.as_blob(contract_name.into(), None, None)];
+    let blob_tx = BlobTransaction::new(identity.clone(), blobs.clone());
+
+    println!("blob_tx: {:#?}", blob_tx);
+    // Send the blob transaction
+    let blob_tx_hash = client
+        .send_tx_blob(&blob_tx)
+        .await
+        .context("cannot send tx")?;
+    println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
+
+    // ----
+    // Prove the state transition
+    // ----
+
+    // The context of the transaction is known once it is included in a block
+    let tx_ctx = fetch_tx_ctx(client, &blob_tx_hash).await?;
+
+    // Build the contract input
+    let inputs = ContractInput {
+        state: initial_state.as_bytes()?,
+        identity: identity.into(),
+        tx_hash: blob_tx_hash,
+        private_input: vec![],
+        tx_ctx: Some(tx_ctx),
+        blobs: blobs.clone(),
+        index: sdk::BlobIndex(0),
+    };
+
+    println!("inputs: {:#?}", inputs);
+
+    print_output(&initial_state, &inputs)?;
+
+    // Generate the zk proof
+    println!("🔍 Proving state transition...");
+    let proof = prover.prove(inputs).await.unwrap();
+
+    let proof_tx = ProofTransaction {
+        proof,
+        contract_name: contract_name.into(),
+    };
+
+    // Send the proof transaction
+    let proof_tx_hash = client
+        .send_tx_proof(&proof_tx)
+        .await
+        .context("failed to send proof")?;
+    println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+    Ok(())
+}
+
+/// Executes the contract locally, then decodes and prints the events it emits.
+fn print_output(initial_state: &SimpleToken, inputs: &ContractInput) -> anyhow::Result<()> {
+    let res = initial_state
+        .clone()
+        .execute(inputs)
+        .map_err(|e| anyhow::anyhow!(e))
+        .context("failed to execute action")?;
+    let output = SimpleTokenOutput::decode(&res.0)
+        .map_err(|e| anyhow::anyhow!(e))
+        .context("failed to decode program output")?;
+    for event in output.events() {
+        println!("🚀 Executed: {}", event);
+    }
+    Ok(())
+}
+
+/// Waits for the blob transaction to be included in a block, and returns its context.
+async fn fetch_tx_ctx(
+    client: &NodeApiHttpClient,
+    tx_hash: &sdk::TxHash,
+) -> anyhow::Result<sdk::TxContext> {
+    for _ in 0..30 {
+        if let Ok(tx) = client.get_unsettled_tx(tx_hash).await {
+            return Ok(tx.tx_context);
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    anyhow::bail!("blob tx {} was not included in a block", tx_hash)
+}
@@ -3,32 +3,44 @@
 extern crate alloc;
 
 use alloc::{
-    collections::btree_map::BTreeMap,
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
     format,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
+use core::fmt;
 
 use borsh::{io::Error, BorshDeserialize, BorshSerialize};
+use ed25519_dalek::{Signature, VerifyingKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use sdk::RunResult;
 
+pub mod merkle;
+
 impl sdk::HyleContract for SimpleToken {
     /// Entry point of the contract's logic
     fn execute(&mut self, contract_input: &sdk::ContractInput) -> RunResult {
         // Parse contract inputs
-        let (action, ctx) =
-            sdk::utils::parse_raw_contract_input::<SimpleTokenAction>(contract_input)?;
+        let (action, ctx) = sdk::utils::parse_contract_input::<SimpleTokenAction>(contract_input)?;
+        check_caller(contract_input, &ctx).map_err(|e| e.to_string())?;
 
-        // Execute the given action
-        let res = match action {
-            SimpleTokenAction::Transfer { recipient, amount } => {
-                self.transfer(&ctx.caller.0, &recipient, amount)?
-            }
-        };
+        // Time-based checks use the height of the block including the transaction
+        self.block_height = contract_input
+            .tx_ctx
+            .as_ref()
+            .map(|tx_ctx| tx_ctx.block_height.0);
+        // Permits are signed for a given contract, and the faucet account is named after it
+        self.contract_name = ctx.contract_name.0.clone();
+
+        // Execute the given action, errors are turned into the sdk's error string only here
+        let events = self
+            .execute_action(&ctx.caller.0, action, &contract_input.private_input)
+            .map_err(|e| e.to_string())?;
 
-        Ok((res, ctx, alloc::vec![]))
+        Ok((SimpleTokenOutput::V1(events).encode(), ctx, alloc::vec![]))
     }
 
     /// In this example, we serialize the full state on-chain.
@@ -42,59 +54,1331 @@ impl sdk::HyleContract for SimpleToken {
 pub struct SimpleToken {
     pub total_supply: u128,
     pub balances: BTreeMap<String, u128>, // Balances for each account
+    pub allowances: BTreeMap<String, BTreeMap<String, u128>>, // Owner -> spender -> allowance
+    pub minter: String,                   // Only identity allowed to mint new tokens
+    pub max_supply: Option<u128>,         // Optional cap on the total supply
+    pub metadata: TokenMetadata,
+    pub admin: String,            // Only identity allowed to use the admin controls
+    pub paused: bool,             // When paused, no transfer can happen
+    pub frozen: BTreeSet<String>, // Accounts that can neither send nor receive tokens
+    pub allow_list_enabled: bool, // When enabled, only allowed accounts can transfer
+    pub allow_list: BTreeSet<String>, // Accounts allowed to transfer
+    pub vesting: BTreeMap<String, VestingSchedule>, // Locked tokens of each account
+    pub snapshot_id: u64,         // Id of the last snapshot, 0 if none was taken
+    /// Balances of each account at past snapshots, as `(snapshot_id, balance)` sorted by id.
+    /// A balance is only recorded when the account changes after a snapshot: it holds for that
+    /// snapshot and all previous ones not recorded.
+    pub checkpoints: BTreeMap<String, Vec<(u64, u128)>>,
+    pub locks: BTreeMap<u64, HashLock>, // Pending hash time-locked transfers
+    pub next_lock_id: u64,              // Id given to the next hash time-locked transfer
+    pub permit_keys: BTreeMap<String, [u8; 32]>, // Ed25519 public key signing the permits of each owner
+    pub permit_nonces: BTreeMap<String, u64>,    // Nonce of the next permit of each owner
+    pub drip: Option<DripConfig>, // Amount anyone can claim from the faucet, if enabled
+    pub last_drips: BTreeMap<String, u64>, // Block height of the last drip of each account
+    /// Height of the block including the transaction being executed, taken from its context.
+    /// It is not part of the on-chain state.
+    #[borsh(skip)]
+    #[serde(skip)]
+    pub block_height: Option<u64>,
+    /// Name of the contract executing the transaction. It is not part of the on-chain state.
+    #[borsh(skip)]
+    #[serde(skip)]
+    pub contract_name: String,
+}
+
+/// Tokens taken from `sender`, that `recipient` can claim by revealing the preimage of `hashlock`
+/// before the `timeout` block height. After it, they can only be refunded to `sender`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HashLock {
+    pub sender: String,
+    pub recipient: String,
+    pub amount: u128,
+    pub hashlock: [u8; 32], // sha256 of the preimage
+    pub timeout: u64,
+}
+
+/// The message signed by an owner to let `spender` transfer `amount` of its tokens,
+/// without sending a blob itself. It is only valid until the `deadline` block height.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PermitPayload {
+    pub contract: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: u128,
+    pub nonce: u64,
+    pub deadline: u64,
+}
+
+/// Amount any identity can claim from the `faucet.<contract_name>` account,
+/// at most once every `cooldown` blocks.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DripConfig {
+    pub amount: u128,
+    pub cooldown: u64,
+}
+
+/// Tokens of an account released linearly between `start + cliff` and `start + duration`.
+/// All values are block heights or numbers of blocks.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VestingSchedule {
+    pub total: u128,
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+/// Descriptive information about the token, set at registration
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    /// Number of decimals used to display amounts: with 2 decimals, 1250 base units are `12.5`
+    pub decimals: u8,
 }
 
 /// Enum representing possible calls to the contract functions.
 #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub enum SimpleTokenAction {
-    Transfer { recipient: String, amount: u128 },
+    Transfer {
+        recipient: String,
+        amount: u128,
+    },
+    Approve {
+        spender: String,
+        amount: u128,
+    },
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: u128,
+    },
+    Mint {
+        recipient: String,
+        amount: u128,
+    },
+    Burn {
+        amount: u128,
+    },
+    BatchTransfer {
+        transfers: Vec<(String, u128)>,
+    },
+    Admin(AdminAction),
+    /// Transfers `amount` to `beneficiary`, locked by a vesting schedule starting at the current block.
+    /// Only the admin can call it.
+    CreateVesting {
+        beneficiary: String,
+        amount: u128,
+        cliff: u64,
+        duration: u64,
+    },
+    /// Records the current balances, to be read later with `balance_at`. Only the admin can call it.
+    Snapshot {},
+    /// Locks `amount` tokens of the caller, for `recipient` to claim before the `timeout` block height
+    LockTransfer {
+        recipient: String,
+        amount: u128,
+        hashlock: [u8; 32],
+        timeout: u64,
+    },
+    /// Claims a locked transfer. The preimage of the hashlock is sent as private input,
+    /// and revealed in the `Claimed` event so that the counterparty of a swap can use it.
+    Claim {
+        lock_id: u64,
+    },
+    /// Gives a locked transfer back to its sender, once its timeout is reached
+    Refund {
+        lock_id: u64,
+    },
+    /// Sets the ed25519 public key verifying the permits of the caller
+    SetPermitKey {
+        public_key: [u8; 32],
+    },
+    /// Approves `spender` on behalf of `owner`, with an ed25519 signature of the matching `PermitPayload`
+    Permit {
+        owner: String,
+        spender: String,
+        amount: u128,
+        nonce: u64,
+        deadline: u64,
+        signature: Vec<u8>,
+    },
+    /// Gives the configured drip amount from the faucet to the caller
+    Drip {},
+}
+
+/// Enum representing the controls only available to the admin of the contract.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum AdminAction {
+    Pause,
+    Unpause,
+    Freeze {
+        account: String,
+    },
+    Unfreeze {
+        account: String,
+    },
+    EnableAllowList,
+    DisableAllowList,
+    Allow {
+        account: String,
+    },
+    Disallow {
+        account: String,
+    },
+    /// Lets anyone claim `amount` from the faucet, at most once every `cooldown` blocks
+    SetDrip {
+        amount: u128,
+        cooldown: u64,
+    },
+    DisableDrip,
+}
+
+/// Enum representing the events emitted by the contract functions.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum SimpleTokenEvent {
+    Transferred {
+        sender: String,
+        recipient: String,
+        amount: u128,
+    },
+    Approved {
+        owner: String,
+        spender: String,
+        amount: u128,
+    },
+    Minted {
+        recipient: String,
+        amount: u128,
+    },
+    Burned {
+        owner: String,
+        amount: u128,
+    },
+    VestingCreated {
+        beneficiary: String,
+        schedule: VestingSchedule,
+    },
+    Snapshot {
+        id: u64,
+    },
+    Locked {
+        lock_id: u64,
+        lock: HashLock,
+    },
+    Claimed {
+        lock_id: u64,
+        preimage: Vec<u8>,
+    },
+    Refunded {
+        lock_id: u64,
+    },
+    PermitKeySet {
+        owner: String,
+        public_key: [u8; 32],
+    },
+    Admin(AdminAction),
+}
+
+/// The program output of the contract: the events emitted by the executed action.
+/// It is versioned so that indexers can keep decoding the outputs of older versions of the contract.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum SimpleTokenOutput {
+    V1(Vec<SimpleTokenEvent>),
+}
+
+/// Enum representing the errors returned by the contract functions.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum SimpleTokenError {
+    AccountNotFound(String),
+    InsufficientBalance {
+        account: String,
+        balance: u128,
+        amount: u128,
+    },
+    InsufficientAllowance {
+        owner: String,
+        spender: String,
+        allowance: u128,
+        amount: u128,
+    },
+    Overflow,
+    Unauthorized(String),
+    MaxSupplyExceeded {
+        max_supply: u128,
+        total_supply: u128,
+    },
+    SupplyMismatch {
+        total_supply: u128,
+        balances: u128,
+    },
+    InvalidProof(String),
+    Paused,
+    AccountFrozen(String),
+    NotAllowed(String),
+    VestingLocked {
+        account: String,
+        locked: u128,
+        amount: u128,
+    },
+    VestingExists(String),
+    MissingTxContext,
+    SnapshotNotFound(u64),
+    LockNotFound(u64),
+    InvalidPreimage(u64),
+    LockExpired {
+        lock_id: u64,
+        timeout: u64,
+    },
+    LockNotExpired {
+        lock_id: u64,
+        timeout: u64,
+    },
+    InvalidTimeout {
+        timeout: u64,
+        block_height: u64,
+    },
+    PermitKeyNotSet(String),
+    InvalidNonce {
+        owner: String,
+        expected: u64,
+        nonce: u64,
+    },
+    InvalidSignature,
+    InvalidPublicKey,
+    PermitExpired(u64),
+    DripDisabled,
+    DripCooldown {
+        account: String,
+        available_at: u64,
+    },
+}
+
+impl fmt::Display for SimpleTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimpleTokenError::AccountNotFound(account) => write!(f, "Account {account} not found"),
+            SimpleTokenError::InsufficientBalance {
+                account,
+                balance,
+                amount,
+            } => write!(
+                f,
+                "Insufficient balance: {account} has {balance} but needs {amount}"
+            ),
+            SimpleTokenError::InsufficientAllowance {
+                owner,
+                spender,
+                allowance,
+                amount,
+            } => write!(
+                f,
+                "Insufficient allowance: {spender} can spend {allowance} from {owner} but needs {amount}"
+            ),
+            SimpleTokenError::Overflow => write!(f, "Arithmetic overflow"),
+            SimpleTokenError::Unauthorized(caller) => {
+                write!(f, "{caller} is not allowed to perform this action")
+            }
+            SimpleTokenError::MaxSupplyExceeded {
+                max_supply,
+                total_supply,
+            } => write!(
+                f,
+                "Total supply {total_supply} would exceed max supply {max_supply}"
+            ),
+            SimpleTokenError::SupplyMismatch {
+                total_supply,
+                balances,
+            } => write!(
+                f,
+                "Total supply {total_supply} does not match balances {balances}"
+            ),
+            SimpleTokenError::InvalidProof(reason) => write!(f, "{reason}"),
+            SimpleTokenError::Paused => write!(f, "Transfers are paused"),
+            SimpleTokenError::AccountFrozen(account) => write!(f, "Account {account} is frozen"),
+            SimpleTokenError::NotAllowed(account) => {
+                write!(f, "Account {account} is not in the allow-list")
+            }
+            SimpleTokenError::VestingLocked {
+                account,
+                locked,
+                amount,
+            } => write!(
+                f,
+                "Cannot spend {amount} from {account}: {locked} are still locked by vesting"
+            ),
+            SimpleTokenError::VestingExists(account) => {
+                write!(f, "Account {account} already has tokens vesting")
+            }
+            SimpleTokenError::MissingTxContext => {
+                write!(f, "Missing transaction context to check vesting")
+            }
+            SimpleTokenError::SnapshotNotFound(id) => write!(f, "Snapshot {id} not found"),
+            SimpleTokenError::LockNotFound(lock_id) => write!(f, "Lock {lock_id} not found"),
+            SimpleTokenError::InvalidPreimage(lock_id) => {
+                write!(f, "Invalid preimage for lock {lock_id}")
+            }
+            SimpleTokenError::LockExpired { lock_id, timeout } => {
+                write!(f, "Lock {lock_id} expired at block {timeout}")
+            }
+            SimpleTokenError::LockNotExpired { lock_id, timeout } => {
+                write!(f, "Lock {lock_id} cannot be refunded before block {timeout}")
+            }
+            SimpleTokenError::InvalidTimeout {
+                timeout,
+                block_height,
+            } => write!(
+                f,
+                "Timeout {timeout} must be after the current block {block_height}"
+            ),
+            SimpleTokenError::PermitKeyNotSet(owner) => {
+                write!(f, "No permit key set for {owner}")
+            }
+            SimpleTokenError::InvalidNonce {
+                owner,
+                expected,
+                nonce,
+            } => write!(
+                f,
+                "Invalid permit nonce for {owner}: expected {expected} but got {nonce}"
+            ),
+            SimpleTokenError::InvalidSignature => write!(f, "Invalid permit signature"),
+            SimpleTokenError::InvalidPublicKey => write!(f, "Invalid ed25519 public key"),
+            SimpleTokenError::PermitExpired(deadline) => {
+                write!(f, "Permit expired at block {deadline}")
+            }
+            SimpleTokenError::DripDisabled => write!(f, "Drip is disabled"),
+            SimpleTokenError::DripCooldown {
+                account,
+                available_at,
+            } => write!(f, "{account} cannot drip again before block {available_at}"),
+        }
+    }
+}
+
+impl fmt::Display for SimpleTokenEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimpleTokenEvent::Transferred {
+                sender,
+                recipient,
+                amount,
+            } => write!(f, "Transferred {amount} from {sender} to {recipient}"),
+            SimpleTokenEvent::Approved {
+                owner,
+                spender,
+                amount,
+            } => write!(f, "Approved {amount} from {owner} to {spender}"),
+            SimpleTokenEvent::Minted { recipient, amount } => {
+                write!(f, "Minted {amount} to {recipient}")
+            }
+            SimpleTokenEvent::Burned { owner, amount } => write!(f, "Burned {amount} from {owner}"),
+            SimpleTokenEvent::VestingCreated {
+                beneficiary,
+                schedule,
+            } => write!(
+                f,
+                "Vesting {} to {beneficiary} from block {}, cliff {} blocks, duration {} blocks",
+                schedule.total, schedule.start, schedule.cliff, schedule.duration
+            ),
+            SimpleTokenEvent::Snapshot { id } => write!(f, "Took snapshot {id}"),
+            SimpleTokenEvent::Locked { lock_id, lock } => write!(
+                f,
+                "Locked {} from {} to {} as lock {lock_id}, until block {}",
+                lock.amount, lock.sender, lock.recipient, lock.timeout
+            ),
+            SimpleTokenEvent::Claimed { lock_id, preimage } => {
+                write!(
+                    f,
+                    "Claimed lock {lock_id} with preimage {}",
+                    hex::encode(preimage)
+                )
+            }
+            SimpleTokenEvent::Refunded { lock_id } => write!(f, "Refunded lock {lock_id}"),
+            SimpleTokenEvent::PermitKeySet { owner, public_key } => write!(
+                f,
+                "Set permit key of {owner} to {}",
+                hex::encode(public_key)
+            ),
+            SimpleTokenEvent::Admin(action) => match action {
+                AdminAction::Pause => write!(f, "Transfers paused"),
+                AdminAction::Unpause => write!(f, "Transfers unpaused"),
+                AdminAction::Freeze { account } => write!(f, "Froze {account}"),
+                AdminAction::Unfreeze { account } => write!(f, "Unfroze {account}"),
+                AdminAction::EnableAllowList => write!(f, "Allow-list enabled"),
+                AdminAction::DisableAllowList => write!(f, "Allow-list disabled"),
+                AdminAction::Allow { account } => write!(f, "Added {account} to the allow-list"),
+                AdminAction::Disallow { account } => {
+                    write!(f, "Removed {account} from the allow-list")
+                }
+                AdminAction::SetDrip { amount, cooldown } => {
+                    write!(f, "Drip set to {amount} every {cooldown} blocks")
+                }
+                AdminAction::DisableDrip => write!(f, "Drip disabled"),
+            },
+        }
+    }
+}
+
+impl SimpleTokenOutput {
+    /// Encodes the output as the string returned by `execute`: the sdk expects a string,
+    /// so the borsh encoding is hex-encoded.
+    pub fn encode(&self) -> String {
+        hex::encode(borsh::to_vec(self).expect("Failed to encode SimpleTokenOutput"))
+    }
+
+    pub fn decode(output: &str) -> Result<Self, String> {
+        let bytes = hex::decode(output).map_err(|e| e.to_string())?;
+        borsh::from_slice(&bytes).map_err(|e| e.to_string())
+    }
+
+    pub fn events(&self) -> &[SimpleTokenEvent] {
+        match self {
+            SimpleTokenOutput::V1(events) => events,
+        }
+    }
 }
 
 impl SimpleToken {
     /// Creates a new token with the specified initial supply.
-    pub fn new(initial_supply: u128, faucet_id: String) -> Self {
+    /// Only `minter` can create new tokens afterwards, up to `max_supply` if set,
+    /// and only `admin` can pause transfers, freeze accounts and manage the allow-list.
+    pub fn new(
+        initial_supply: u128,
+        faucet_id: String,
+        minter: String,
+        max_supply: Option<u128>,
+        metadata: TokenMetadata,
+        admin: String,
+    ) -> Self {
         let mut balances = BTreeMap::new();
         balances.insert(faucet_id, initial_supply); // Assign initial supply to faucet
         SimpleToken {
             total_supply: initial_supply,
             balances,
+            allowances: BTreeMap::new(),
+            minter,
+            max_supply,
+            metadata,
+            admin,
+            paused: false,
+            frozen: BTreeSet::new(),
+            allow_list_enabled: false,
+            allow_list: BTreeSet::new(),
+            vesting: BTreeMap::new(),
+            snapshot_id: 0,
+            checkpoints: BTreeMap::new(),
+            locks: BTreeMap::new(),
+            next_lock_id: 0,
+            permit_keys: BTreeMap::new(),
+            permit_nonces: BTreeMap::new(),
+            drip: None,
+            last_drips: BTreeMap::new(),
+            block_height: None,
+            contract_name: String::new(),
         }
     }
 
     pub fn as_bytes(&self) -> Result<Vec<u8>, Error> {
         borsh::to_vec(self)
     }
+
+    /// Applies `action` sent by `caller`, then checks the supply invariants.
+    /// `private_input` holds the data of the action that is not sent on-chain.
+    pub fn execute_action(
+        &mut self,
+        caller: &str,
+        action: SimpleTokenAction,
+        private_input: &[u8],
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let events = match action {
+            SimpleTokenAction::Transfer { recipient, amount } => {
+                self.transfer(caller, &recipient, amount)?
+            }
+            SimpleTokenAction::Approve { spender, amount } => {
+                self.approve(caller, &spender, amount)?
+            }
+            SimpleTokenAction::TransferFrom {
+                owner,
+                recipient,
+                amount,
+            } => self.transfer_from(caller, &owner, &recipient, amount)?,
+            SimpleTokenAction::Mint { recipient, amount } => {
+                self.mint(caller, &recipient, amount)?
+            }
+            SimpleTokenAction::Burn { amount } => self.burn(caller, amount)?,
+            SimpleTokenAction::BatchTransfer { transfers } => {
+                self.batch_transfer(caller, &transfers)?
+            }
+            SimpleTokenAction::Admin(action) => self.admin(caller, action)?,
+            SimpleTokenAction::CreateVesting {
+                beneficiary,
+                amount,
+                cliff,
+                duration,
+            } => self.create_vesting(caller, &beneficiary, amount, cliff, duration)?,
+            SimpleTokenAction::Snapshot {} => self.snapshot(caller)?,
+            SimpleTokenAction::LockTransfer {
+                recipient,
+                amount,
+                hashlock,
+                timeout,
+            } => self.lock_transfer(caller, &recipient, amount, hashlock, timeout)?,
+            SimpleTokenAction::Claim { lock_id } => self.claim(lock_id, private_input)?,
+            SimpleTokenAction::Refund { lock_id } => self.refund(lock_id)?,
+            SimpleTokenAction::SetPermitKey { public_key } => {
+                self.set_permit_key(caller, public_key)?
+            }
+            SimpleTokenAction::Permit {
+                owner,
+                spender,
+                amount,
+                nonce,
+                deadline,
+                signature,
+            } => {
+                let payload = PermitPayload {
+                    contract: self.contract_name.clone(),
+                    owner,
+                    spender,
+                    amount,
+                    nonce,
+                    deadline,
+                };
+                self.permit(payload, &signature)?
+            }
+            SimpleTokenAction::Drip {} => self.drip(caller)?,
+        };
+
+        self.check_supply()?;
+
+        Ok(events)
+    }
 }
 
 impl SimpleToken {
-    pub fn balance_of(&self, account: &str) -> Result<u128, String> {
+    pub fn balance_of(&self, account: &str) -> Result<u128, SimpleTokenError> {
         match self.balances.get(account) {
             Some(&balance) => Ok(balance),
-            None => Err(format!("Account {account} not found")),
+            None => Err(SimpleTokenError::AccountNotFound(account.to_string())),
         }
     }
 
+    pub fn metadata(&self) -> &TokenMetadata {
+        &self.metadata
+    }
+
+    /// Returns the amount `spender` is still allowed to transfer on behalf of `owner`.
+    pub fn allowance(&self, owner: &str, spender: &str) -> u128 {
+        self.allowances
+            .get(owner)
+            .and_then(|spenders| spenders.get(spender))
+            .copied()
+            .unwrap_or(0)
+    }
+
     pub fn transfer(
         &mut self,
         sender: &str,
         recipient: &str,
         amount: u128,
-    ) -> Result<String, String> {
-        let sender_balance = self.balance_of(sender)?;
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        self.check_transfer_allowed(sender, recipient)?;
 
-        if sender_balance < amount {
-            return Err("Insufficient balance".to_string());
+        self.debit(sender, amount)?;
+        self.credit(recipient, amount)?;
+
+        Ok(vec![SimpleTokenEvent::Transferred {
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            amount,
+        }])
+    }
+
+    /// Transfers tokens to several recipients at once. Either all transfers are applied, or none.
+    pub fn batch_transfer(
+        &mut self,
+        sender: &str,
+        transfers: &[(String, u128)],
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let total = transfers
+            .iter()
+            .try_fold(0u128, |sum, (_, amount)| sum.checked_add(*amount))
+            .ok_or(SimpleTokenError::Overflow)?;
+        for (recipient, _) in transfers {
+            self.check_transfer_allowed(sender, recipient)?;
         }
 
-        *self.balances.entry(sender.to_string()).or_insert(0) -= amount;
-        *self.balances.entry(recipient.to_string()).or_insert(0) += amount;
+        // Once the total is debited, credits cannot overflow as balances sum up to the total supply
+        self.debit(sender, total)?;
+        let mut events = Vec::with_capacity(transfers.len());
+        for (recipient, amount) in transfers {
+            self.credit(recipient, *amount)?;
+            events.push(SimpleTokenEvent::Transferred {
+                sender: sender.to_string(),
+                recipient: recipient.clone(),
+                amount: *amount,
+            });
+        }
+
+        Ok(events)
+    }
+
+    /// Sets the allowance of `spender` over the tokens of `owner`, replacing any previous one.
+    /// Frozen accounts, and accounts missing from an enabled allow-list, can neither give nor get one.
+    pub fn approve(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        amount: u128,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        self.check_account_allowed(owner)?;
+        self.check_account_allowed(spender)?;
+
+        if amount == 0 {
+            if let Some(spenders) = self.allowances.get_mut(owner) {
+                spenders.remove(spender);
+                if spenders.is_empty() {
+                    self.allowances.remove(owner);
+                }
+            }
+        } else {
+            self.allowances
+                .entry(owner.to_string())
+                .or_default()
+                .insert(spender.to_string(), amount);
+        }
 
-        Ok(format!(
-            "Transferred {amount} from {sender} to {recipient}",
-            amount = amount,
-            sender = sender,
-            recipient = recipient
-        ))
+        Ok(vec![SimpleTokenEvent::Approved {
+            owner: owner.to_string(),
+            spender: spender.to_string(),
+            amount,
+        }])
+    }
+
+    /// Transfers tokens from `owner` to `recipient`, consuming the allowance granted to `spender`.
+    /// The admin controls apply to the spender as well as to the owner and the recipient.
+    pub fn transfer_from(
+        &mut self,
+        spender: &str,
+        owner: &str,
+        recipient: &str,
+        amount: u128,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        self.check_account_allowed(spender)?;
+        let allowance = self.allowance(owner, spender);
+        if allowance < amount {
+            return Err(SimpleTokenError::InsufficientAllowance {
+                owner: owner.to_string(),
+                spender: spender.to_string(),
+                allowance,
+                amount,
+            });
+        }
+
+        let mut events = self.transfer(owner, recipient, amount)?;
+        events.extend(self.approve(owner, spender, allowance - amount)?);
+
+        Ok(events)
+    }
+
+    /// Creates `amount` new tokens on `recipient`'s account. Only the minter can call it.
+    /// The admin controls apply as for a transfer from the minter.
+    pub fn mint(
+        &mut self,
+        caller: &str,
+        recipient: &str,
+        amount: u128,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        if caller != self.minter {
+            return Err(SimpleTokenError::Unauthorized(caller.to_string()));
+        }
+        self.check_transfer_allowed(caller, recipient)?;
+
+        let total_supply = self
+            .total_supply
+            .checked_add(amount)
+            .ok_or(SimpleTokenError::Overflow)?;
+        if let Some(max_supply) = self.max_supply {
+            if total_supply > max_supply {
+                return Err(SimpleTokenError::MaxSupplyExceeded {
+                    max_supply,
+                    total_supply,
+                });
+            }
+        }
+
+        self.credit(recipient, amount)?;
+        self.total_supply = total_supply;
+
+        Ok(vec![SimpleTokenEvent::Minted {
+            recipient: recipient.to_string(),
+            amount,
+        }])
+    }
+
+    /// Destroys `amount` tokens from `owner`'s account. Only the minter can call it, on its own tokens.
+    pub fn burn(
+        &mut self,
+        owner: &str,
+        amount: u128,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        if owner != self.minter {
+            return Err(SimpleTokenError::Unauthorized(owner.to_string()));
+        }
+        self.check_not_paused()?;
+        self.check_account_allowed(owner)?;
+
+        self.debit(owner, amount)?;
+        self.total_supply -= amount;
+
+        Ok(vec![SimpleTokenEvent::Burned {
+            owner: owner.to_string(),
+            amount,
+        }])
+    }
+
+    /// Transfers `amount` from `sender` to `beneficiary`, locked by a vesting schedule starting
+    /// at the current block height. An account can only have one schedule still vesting.
+    /// Only the admin can call it: a schedule restricts what the beneficiary can spend.
+    pub fn create_vesting(
+        &mut self,
+        sender: &str,
+        beneficiary: &str,
+        amount: u128,
+        cliff: u64,
+        duration: u64,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        if sender != self.admin {
+            return Err(SimpleTokenError::Unauthorized(sender.to_string()));
+        }
+        let block_height = self
+            .block_height
+            .ok_or(SimpleTokenError::MissingTxContext)?;
+        if self
+            .vesting
+            .get(beneficiary)
+            .is_some_and(|schedule| schedule.locked_at(block_height) > 0)
+        {
+            return Err(SimpleTokenError::VestingExists(beneficiary.to_string()));
+        }
+
+        let mut events = self.transfer(sender, beneficiary, amount)?;
+        let schedule = VestingSchedule {
+            total: amount,
+            start: block_height,
+            cliff,
+            duration,
+        };
+        self.vesting
+            .insert(beneficiary.to_string(), schedule.clone());
+        events.push(SimpleTokenEvent::VestingCreated {
+            beneficiary: beneficiary.to_string(),
+            schedule,
+        });
+
+        Ok(events)
+    }
+
+    /// Returns the amount of tokens of `account` still locked by its vesting schedule.
+    /// The block height is needed while the account has a schedule, until it is dropped once fully vested.
+    pub fn locked_balance(&self, account: &str) -> Result<u128, SimpleTokenError> {
+        match self.vesting.get(account) {
+            Some(schedule) => {
+                let block_height = self
+                    .block_height
+                    .ok_or(SimpleTokenError::MissingTxContext)?;
+                Ok(schedule.locked_at(block_height))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Takes a new snapshot of the balances. Only the admin can call it.
+    /// Nothing is copied here: balances are recorded when they change afterwards.
+    pub fn snapshot(&mut self, caller: &str) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        if caller != self.admin {
+            return Err(SimpleTokenError::Unauthorized(caller.to_string()));
+        }
+
+        self.snapshot_id = self
+            .snapshot_id
+            .checked_add(1)
+            .ok_or(SimpleTokenError::Overflow)?;
+
+        Ok(vec![SimpleTokenEvent::Snapshot {
+            id: self.snapshot_id,
+        }])
+    }
+
+    /// Returns the balance of `account` at the time snapshot `snapshot_id` was taken.
+    pub fn balance_at(&self, account: &str, snapshot_id: u64) -> Result<u128, SimpleTokenError> {
+        if snapshot_id == 0 || snapshot_id > self.snapshot_id {
+            return Err(SimpleTokenError::SnapshotNotFound(snapshot_id));
+        }
+
+        // The first balance recorded at or after the snapshot is the one it holds,
+        // if the account did not change since then, it is its current balance.
+        let recorded = self.checkpoints.get(account).and_then(|checkpoints| {
+            let i = checkpoints.partition_point(|(id, _)| *id < snapshot_id);
+            checkpoints.get(i).map(|(_, balance)| *balance)
+        });
+        Ok(recorded.unwrap_or_else(|| self.balances.get(account).copied().unwrap_or(0)))
+    }
+
+    /// Records the balance of `account` for the last snapshot, before it changes.
+    fn checkpoint(&mut self, account: &str) {
+        if self.snapshot_id == 0 {
+            return;
+        }
+        let balance = self.balances.get(account).copied().unwrap_or(0);
+        let checkpoints = self.checkpoints.entry(account.to_string()).or_default();
+        if checkpoints
+            .last()
+            .map_or(true, |(id, _)| *id < self.snapshot_id)
+        {
+            checkpoints.push((self.snapshot_id, balance));
+        }
+    }
+
+    /// Takes `amount` tokens from `sender`, that `recipient` can claim with the preimage of
+    /// `hashlock` until the `timeout` block height.
+    pub fn lock_transfer(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: u128,
+        hashlock: [u8; 32],
+        timeout: u64,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let block_height = self
+            .block_height
+            .ok_or(SimpleTokenError::MissingTxContext)?;
+        if timeout <= block_height {
+            return Err(SimpleTokenError::InvalidTimeout {
+                timeout,
+                block_height,
+            });
+        }
+        self.check_transfer_allowed(sender, recipient)?;
+
+        self.debit(sender, amount)?;
+        let lock = HashLock {
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            amount,
+            hashlock,
+            timeout,
+        };
+        let lock_id = self.next_lock_id;
+        self.locks.insert(lock_id, lock.clone());
+        self.next_lock_id = lock_id.checked_add(1).ok_or(SimpleTokenError::Overflow)?;
+
+        Ok(vec![SimpleTokenEvent::Locked { lock_id, lock }])
+    }
+
+    /// Gives locked tokens to their recipient, if `preimage` matches the hashlock before the timeout.
+    pub fn claim(
+        &mut self,
+        lock_id: u64,
+        preimage: &[u8],
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let block_height = self
+            .block_height
+            .ok_or(SimpleTokenError::MissingTxContext)?;
+        let lock = self
+            .locks
+            .get(&lock_id)
+            .ok_or(SimpleTokenError::LockNotFound(lock_id))?;
+        if block_height >= lock.timeout {
+            return Err(SimpleTokenError::LockExpired {
+                lock_id,
+                timeout: lock.timeout,
+            });
+        }
+        if hash_preimage(preimage) != lock.hashlock {
+            return Err(SimpleTokenError::InvalidPreimage(lock_id));
+        }
+        self.check_transfer_allowed(&lock.sender, &lock.recipient)?;
+
+        let lock = self.locks.remove(&lock_id).expect("lock exists");
+        self.credit(&lock.recipient, lock.amount)?;
+
+        Ok(vec![
+            SimpleTokenEvent::Claimed {
+                lock_id,
+                preimage: preimage.to_vec(),
+            },
+            SimpleTokenEvent::Transferred {
+                sender: lock.sender,
+                recipient: lock.recipient,
+                amount: lock.amount,
+            },
+        ])
+    }
+
+    /// Gives locked tokens back to their sender, once the timeout is reached.
+    pub fn refund(&mut self, lock_id: u64) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let block_height = self
+            .block_height
+            .ok_or(SimpleTokenError::MissingTxContext)?;
+        let lock = self
+            .locks
+            .get(&lock_id)
+            .ok_or(SimpleTokenError::LockNotFound(lock_id))?;
+        if block_height < lock.timeout {
+            return Err(SimpleTokenError::LockNotExpired {
+                lock_id,
+                timeout: lock.timeout,
+            });
+        }
+        self.check_not_paused()?;
+        self.check_account_allowed(&lock.sender)?;
+
+        let lock = self.locks.remove(&lock_id).expect("lock exists");
+        self.credit(&lock.sender, lock.amount)?;
+
+        Ok(vec![SimpleTokenEvent::Refunded { lock_id }])
+    }
+
+    /// Sets the public key verifying the permits signed by `owner`.
+    pub fn set_permit_key(
+        &mut self,
+        owner: &str,
+        public_key: [u8; 32],
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        VerifyingKey::from_bytes(&public_key).map_err(|_| SimpleTokenError::InvalidPublicKey)?;
+        self.permit_keys.insert(owner.to_string(), public_key);
+
+        Ok(vec![SimpleTokenEvent::PermitKeySet {
+            owner: owner.to_string(),
+            public_key,
+        }])
+    }
+
+    /// Returns the nonce the next permit of `owner` must be signed with.
+    pub fn permit_nonce(&self, owner: &str) -> u64 {
+        self.permit_nonces.get(owner).copied().unwrap_or(0)
+    }
+
+    /// Approves a spender with a permit signed by the owner, consuming the owner's nonce.
+    pub fn permit(
+        &mut self,
+        payload: PermitPayload,
+        signature: &[u8],
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let block_height = self
+            .block_height
+            .ok_or(SimpleTokenError::MissingTxContext)?;
+        if block_height > payload.deadline {
+            return Err(SimpleTokenError::PermitExpired(payload.deadline));
+        }
+        let expected = self.permit_nonce(&payload.owner);
+        if payload.nonce != expected {
+            return Err(SimpleTokenError::InvalidNonce {
+                owner: payload.owner,
+                expected,
+                nonce: payload.nonce,
+            });
+        }
+
+        let public_key = self
+            .permit_keys
+            .get(&payload.owner)
+            .ok_or_else(|| SimpleTokenError::PermitKeyNotSet(payload.owner.clone()))?;
+        let verifying_key =
+            VerifyingKey::from_bytes(public_key).map_err(|_| SimpleTokenError::InvalidPublicKey)?;
+        let signature =
+            Signature::from_slice(signature).map_err(|_| SimpleTokenError::InvalidSignature)?;
+        verifying_key
+            .verify_strict(&payload.signing_bytes(), &signature)
+            .map_err(|_| SimpleTokenError::InvalidSignature)?;
+
+        self.permit_nonces
+            .insert(payload.owner.clone(), expected + 1);
+        self.approve(&payload.owner, &payload.spender, payload.amount)
+    }
+
+    /// Transfers the drip amount from the faucet to `recipient`, at most once per cooldown.
+    pub fn drip(&mut self, recipient: &str) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let block_height = self
+            .block_height
+            .ok_or(SimpleTokenError::MissingTxContext)?;
+        let drip = self.drip.clone().ok_or(SimpleTokenError::DripDisabled)?;
+        if let Some(last_drip) = self.last_drips.get(recipient) {
+            let available_at = last_drip.saturating_add(drip.cooldown);
+            if block_height < available_at {
+                return Err(SimpleTokenError::DripCooldown {
+                    account: recipient.to_string(),
+                    available_at,
+                });
+            }
+        }
+
+        let faucet = format!("faucet.{}", self.contract_name);
+        let events = self.transfer(&faucet, recipient, drip.amount)?;
+        self.last_drips.insert(recipient.to_string(), block_height);
+
+        Ok(events)
+    }
+
+    /// Applies an admin control. Only the admin can call it.
+    pub fn admin(
+        &mut self,
+        caller: &str,
+        action: AdminAction,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        if caller != self.admin {
+            return Err(SimpleTokenError::Unauthorized(caller.to_string()));
+        }
+
+        match &action {
+            AdminAction::Pause => self.paused = true,
+            AdminAction::Unpause => self.paused = false,
+            AdminAction::Freeze { account } => {
+                self.frozen.insert(account.clone());
+            }
+            AdminAction::Unfreeze { account } => {
+                self.frozen.remove(account);
+            }
+            AdminAction::EnableAllowList => self.allow_list_enabled = true,
+            AdminAction::DisableAllowList => self.allow_list_enabled = false,
+            AdminAction::Allow { account } => {
+                self.allow_list.insert(account.clone());
+            }
+            AdminAction::Disallow { account } => {
+                self.allow_list.remove(account);
+            }
+            AdminAction::SetDrip { amount, cooldown } => {
+                self.drip = Some(DripConfig {
+                    amount: *amount,
+                    cooldown: *cooldown,
+                });
+            }
+            AdminAction::DisableDrip => self.drip = None,
+        }
+
+        Ok(vec![SimpleTokenEvent::Admin(action)])
+    }
+
+    /// Checks the admin controls for a transfer from `sender` to `recipient`.
+    pub fn check_transfer_allowed(
+        &self,
+        sender: &str,
+        recipient: &str,
+    ) -> Result<(), SimpleTokenError> {
+        self.check_not_paused()?;
+        self.check_account_allowed(sender)?;
+        self.check_account_allowed(recipient)
+    }
+
+    /// Checks that the token is not paused: no tokens move, nor are created or destroyed.
+    pub fn check_not_paused(&self) -> Result<(), SimpleTokenError> {
+        if self.paused {
+            return Err(SimpleTokenError::Paused);
+        }
+        Ok(())
+    }
+
+    /// Checks that `account` is neither frozen nor missing from an enabled allow-list.
+    pub fn check_account_allowed(&self, account: &str) -> Result<(), SimpleTokenError> {
+        if self.frozen.contains(account) {
+            return Err(SimpleTokenError::AccountFrozen(account.to_string()));
+        }
+        if self.allow_list_enabled && !self.allow_list.contains(account) {
+            return Err(SimpleTokenError::NotAllowed(account.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Checks that the total supply matches the balances, including locked transfers,
+    /// and respects the max supply.
+    pub fn check_supply(&self) -> Result<(), SimpleTokenError> {
+        let balances = self
+            .balances
+            .values()
+            .chain(self.locks.values().map(|lock| &lock.amount))
+            .try_fold(0u128, |sum, balance| sum.checked_add(*balance))
+            .ok_or(SimpleTokenError::Overflow)?;
+        if balances != self.total_supply {
+            return Err(SimpleTokenError::SupplyMismatch {
+                total_supply: self.total_supply,
+                balances,
+            });
+        }
+        if let Some(max_supply) = self.max_supply {
+            if self.total_supply > max_supply {
+                return Err(SimpleTokenError::MaxSupplyExceeded {
+                    max_supply,
+                    total_supply: self.total_supply,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn debit(&mut self, account: &str, amount: u128) -> Result<(), SimpleTokenError> {
+        let balance = self.balance_of(account)?;
+        if balance < amount {
+            return Err(SimpleTokenError::InsufficientBalance {
+                account: account.to_string(),
+                balance,
+                amount,
+            });
+        }
+        self.check_vesting(account, amount, balance - amount)?;
+        self.checkpoint(account);
+        self.balances.insert(account.to_string(), balance - amount);
+        Ok(())
+    }
+
+    /// Checks that the `remaining` balance of `account` after debiting `amount` covers the tokens still
+    /// locked by its vesting schedule. A fully vested schedule is dropped, so that later debits do not
+    /// need the block height anymore.
+    fn check_vesting(
+        &mut self,
+        account: &str,
+        amount: u128,
+        remaining: u128,
+    ) -> Result<(), SimpleTokenError> {
+        let Some(schedule) = self.vesting.get(account) else {
+            return Ok(());
+        };
+        // No more than the total of the schedule is ever locked
+        if remaining >= schedule.total {
+            return Ok(());
+        }
+
+        let locked = self.locked_balance(account)?;
+        if remaining < locked {
+            return Err(SimpleTokenError::VestingLocked {
+                account: account.to_string(),
+                locked,
+                amount,
+            });
+        }
+        if locked == 0 {
+            self.vesting.remove(account);
+        }
+        Ok(())
+    }
+
+    fn credit(&mut self, account: &str, amount: u128) -> Result<(), SimpleTokenError> {
+        self.checkpoint(account);
+        let balance = self.balances.entry(account.to_string()).or_insert(0);
+        *balance = balance
+            .checked_add(amount)
+            .ok_or(SimpleTokenError::Overflow)?;
+        Ok(())
+    }
+}
+
+impl PermitPayload {
+    /// The bytes signed by the owner: the borsh encoding of the payload.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        borsh::to_vec(self).expect("Failed to encode PermitPayload")
+    }
+}
+
+/// Computes the hashlock matching `preimage`.
+pub fn hash_preimage(preimage: &[u8]) -> [u8; 32] {
+    Sha256::digest(preimage).into()
+}
+
+impl VestingSchedule {
+    /// Returns the amount released at `block_height`.
+    pub fn vested_at(&self, block_height: u64) -> u128 {
+        let elapsed = block_height.saturating_sub(self.start);
+        if elapsed < self.cliff {
+            return 0;
+        }
+        if elapsed >= self.duration {
+            return self.total;
+        }
+        // total * elapsed / duration, without overflowing
+        let (elapsed, duration) = (elapsed as u128, self.duration as u128);
+        self.total / duration * elapsed + self.total % duration * elapsed / duration
+    }
+
+    /// Returns the amount still locked at `block_height`.
+    pub fn locked_at(&self, block_height: u64) -> u128 {
+        self.total - self.vested_at(block_height)
+    }
+}
+
+impl TokenMetadata {
+    /// Parses a decimal amount such as `12.5` into base units.
+    pub fn parse_amount(&self, amount: &str) -> Result<u128, String> {
+        let invalid = || {
+            format!(
+                "Invalid amount {amount} for a token with {} decimals",
+                self.decimals
+            )
+        };
+        let decimals = self.decimals as usize;
+
+        let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        if (integer.is_empty() && fraction.is_empty())
+            || fraction.len() > decimals
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        // Pad the fractional part with zeros, so that `12.5` with 2 decimals reads as `1250`
+        format!("{integer}{fraction:0<decimals$}")
+            .parse()
+            .map_err(|_| invalid())
+    }
+
+    /// Formats an amount of base units as a decimal number, such as `12.5`.
+    pub fn format_amount(&self, amount: u128) -> String {
+        let Some(unit) = 10u128.checked_pow(self.decimals as u32) else {
+            return amount.to_string();
+        };
+        let integer = amount / unit;
+        let fraction = amount % unit;
+        if fraction == 0 {
+            return integer.to_string();
+        }
+        let fraction = format!("{fraction:0width$}", width = self.decimals as usize);
+        format!("{integer}.{}", fraction.trim_end_matches('0'))
+    }
+}
+
+/// Lets the action be sent as a structured blob, for contracts composing with the token.
+/// Accounts without a `.` in their name, like `simple_ticket_app`, are owned by the contract of that name.
+/// The sdk sets the caller of a structured blob to the contract of its `caller` blob, after checking that
+/// this blob lists it as a callee: a contract-owned account thus acts only through a blob of its contract
+/// in the same transaction, never as the identity of the transaction itself.
+pub fn check_caller(
+    contract_input: &sdk::ContractInput,
+    ctx: &sdk::caller::ExecutionContext,
+) -> Result<(), SimpleTokenError> {
+    if !ctx.caller.0.contains('.') && ctx.caller == contract_input.identity {
+        return Err(SimpleTokenError::Unauthorized(ctx.caller.0.clone()));
+    }
+    Ok(())
+}
+
+/// Actions are sent as structured blobs, so that other contracts of the transaction can be set as their caller.
+impl sdk::ContractAction for SimpleTokenAction {
+    fn as_blob(
+        &self,
+        contract_name: sdk::ContractName,
+        caller: Option<sdk::BlobIndex>,
+        callees: Option<Vec<sdk::BlobIndex>>,
+    ) -> sdk::Blob {
+        sdk::Blob {
+            contract_name,
+            data: sdk::BlobData::from(sdk::StructuredBlobData {
+                caller,
+                callees,
+                parameters: self.clone(),
+            }),
+        }
     }
 }
 
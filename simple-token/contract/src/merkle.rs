@@ -0,0 +1,298 @@
+//! Merkle mode of the token: instead of serializing every balance on-chain, the contract only
+//! commits to the root of a sparse Merkle tree of balances.
+//!
+//! The full balances are kept off-chain by the host, which sends the accounts touched by an
+//! action along with their inclusion proofs as `private_input`. The guest checks each proof
+//! against the current root, then recomputes the root with the updated balance.
+
+use alloc::{
+    collections::btree_map::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use sdk::RunResult;
+
+use crate::{
+    check_caller, SimpleTokenAction, SimpleTokenError, SimpleTokenEvent, SimpleTokenOutput,
+};
+
+pub type Hash = [u8; 32];
+
+/// Depth of the tree: leaves are indexed by the sha256 of the account name.
+pub const DEPTH: usize = 256;
+
+/// Hash of an empty leaf, and of any subtree that only contains empty leaves.
+pub const EMPTY: Hash = [0; 32];
+
+impl sdk::HyleContract for MerkleToken {
+    /// Entry point of the contract's logic
+    fn execute(&mut self, contract_input: &sdk::ContractInput) -> RunResult {
+        // Parse contract inputs
+        let (action, ctx) = sdk::utils::parse_contract_input::<SimpleTokenAction>(contract_input)?;
+        check_caller(contract_input, &ctx).map_err(|e| e.to_string())?;
+
+        // Extract the touched balances, in the order they are used by the action
+        let proofs: Vec<BalanceProof> = borsh::from_slice(&contract_input.private_input)
+            .map_err(|_| "Failed to decode balance proofs".to_string())?;
+
+        // Execute the given action
+        let events = match action {
+            SimpleTokenAction::Transfer { recipient, amount } => self
+                .transfer(&ctx.caller.0, &recipient, amount, proofs)
+                .map_err(|e| e.to_string())?,
+            action => {
+                return Err(format!(
+                    "{} is not supported in Merkle mode, only Transfer is",
+                    action_name(&action)
+                ))
+            }
+        };
+
+        Ok((SimpleTokenOutput::V1(events).encode(), ctx, vec![]))
+    }
+
+    /// Only the root of the balances tree is stored on-chain.
+    fn commit(&self) -> sdk::StateCommitment {
+        sdk::StateCommitment(self.root.to_vec())
+    }
+}
+
+/// Name of the variant of `action`, to report the actions that Merkle mode does not support.
+fn action_name(action: &SimpleTokenAction) -> &'static str {
+    match action {
+        SimpleTokenAction::Transfer { .. } => "Transfer",
+        SimpleTokenAction::Approve { .. } => "Approve",
+        SimpleTokenAction::TransferFrom { .. } => "TransferFrom",
+        SimpleTokenAction::Mint { .. } => "Mint",
+        SimpleTokenAction::Burn { .. } => "Burn",
+        SimpleTokenAction::BatchTransfer { .. } => "BatchTransfer",
+        SimpleTokenAction::Admin(_) => "Admin",
+        SimpleTokenAction::CreateVesting { .. } => "CreateVesting",
+        SimpleTokenAction::Snapshot {} => "Snapshot",
+        SimpleTokenAction::LockTransfer { .. } => "LockTransfer",
+        SimpleTokenAction::Claim { .. } => "Claim",
+        SimpleTokenAction::Refund { .. } => "Refund",
+        SimpleTokenAction::SetPermitKey { .. } => "SetPermitKey",
+        SimpleTokenAction::Permit { .. } => "Permit",
+        SimpleTokenAction::Drip {} => "Drip",
+    }
+}
+
+/// The state of the contract in Merkle mode, only made of the root of the balances tree
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct MerkleToken {
+    pub root: Hash,
+}
+
+/// The balance of an account, along with the proof of its inclusion in the tree.
+/// Accounts that are not in the tree have a balance of 0.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct BalanceProof {
+    pub account: String,
+    pub balance: u128,
+    /// Siblings of the path from the root (index 0) to the leaf (index `DEPTH - 1`)
+    pub siblings: Vec<Hash>,
+}
+
+impl MerkleToken {
+    pub fn new(root: Hash) -> Self {
+        MerkleToken { root }
+    }
+
+    pub fn transfer(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: u128,
+        proofs: Vec<BalanceProof>,
+    ) -> Result<Vec<SimpleTokenEvent>, SimpleTokenError> {
+        let mut proofs = proofs.into_iter();
+
+        let sender_proof = self.next_proof(&mut proofs, sender)?;
+        if sender_proof.balance < amount {
+            return Err(SimpleTokenError::InsufficientBalance {
+                account: sender.to_string(),
+                balance: sender_proof.balance,
+                amount,
+            });
+        }
+        self.root = sender_proof.root_with(sender_proof.balance - amount);
+
+        // The recipient proof is built against the root updated with the sender's new balance
+        let recipient_proof = self.next_proof(&mut proofs, recipient)?;
+        let recipient_balance = recipient_proof
+            .balance
+            .checked_add(amount)
+            .ok_or(SimpleTokenError::Overflow)?;
+        self.root = recipient_proof.root_with(recipient_balance);
+
+        Ok(vec![SimpleTokenEvent::Transferred {
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            amount,
+        }])
+    }
+
+    /// Takes the next proof, and checks that it is the one of `account` against the current root.
+    fn next_proof(
+        &self,
+        proofs: &mut impl Iterator<Item = BalanceProof>,
+        account: &str,
+    ) -> Result<BalanceProof, SimpleTokenError> {
+        let proof = proofs.next().ok_or_else(|| {
+            SimpleTokenError::InvalidProof(format!("Missing balance proof for {account}"))
+        })?;
+        if proof.account != account {
+            return Err(SimpleTokenError::InvalidProof(format!(
+                "Expected balance proof for {account} but got {}",
+                proof.account
+            )));
+        }
+        if proof.siblings.len() != DEPTH || proof.root_with(proof.balance) != self.root {
+            return Err(SimpleTokenError::InvalidProof(format!(
+                "Invalid balance proof for {account}"
+            )));
+        }
+        Ok(proof)
+    }
+}
+
+impl BalanceProof {
+    /// Computes the root of the tree in which the account has the given balance.
+    pub fn root_with(&self, balance: u128) -> Hash {
+        let key = account_key(&self.account);
+        let mut node = leaf_hash(&key, balance);
+        for depth in (0..DEPTH).rev() {
+            node = if bit(&key, depth) {
+                node_hash(&self.siblings[depth], &node)
+            } else {
+                node_hash(&node, &self.siblings[depth])
+            };
+        }
+        node
+    }
+}
+
+impl From<sdk::StateCommitment> for MerkleToken {
+    fn from(state: sdk::StateCommitment) -> Self {
+        let root = state
+            .0
+            .try_into()
+            .expect("Could not decode Merkle token state");
+        MerkleToken { root }
+    }
+}
+
+/// The full balances, kept off-chain to build the proofs sent to the guest.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BalanceTree {
+    pub balances: BTreeMap<String, u128>,
+}
+
+impl BalanceTree {
+    pub fn root(&self) -> Hash {
+        subtree_hash(&self.leaves(), 0)
+    }
+
+    pub fn balance_of(&self, account: &str) -> u128 {
+        self.balances.get(account).copied().unwrap_or(0)
+    }
+
+    pub fn set_balance(&mut self, account: &str, balance: u128) {
+        if balance == 0 {
+            self.balances.remove(account);
+        } else {
+            self.balances.insert(account.to_string(), balance);
+        }
+    }
+
+    /// Builds the inclusion proof of `account` in the current tree.
+    pub fn proof(&self, account: &str) -> BalanceProof {
+        let key = account_key(account);
+        let leaves = self.leaves();
+        let mut subtree = leaves.as_slice();
+        let mut siblings = vec![EMPTY; DEPTH];
+
+        for (depth, sibling) in siblings.iter_mut().enumerate() {
+            let (left, right) = subtree.split_at(subtree.partition_point(|(k, _)| !bit(k, depth)));
+            if bit(&key, depth) {
+                *sibling = subtree_hash(left, depth + 1);
+                subtree = right;
+            } else {
+                *sibling = subtree_hash(right, depth + 1);
+                subtree = left;
+            }
+        }
+
+        BalanceProof {
+            account: account.to_string(),
+            balance: self.balance_of(account),
+            siblings,
+        }
+    }
+
+    /// Leaves of the tree, sorted by key so that each subtree is a contiguous slice.
+    fn leaves(&self) -> Vec<(Hash, Hash)> {
+        let mut leaves: Vec<(Hash, Hash)> = self
+            .balances
+            .iter()
+            .map(|(account, balance)| {
+                let key = account_key(account);
+                (key, leaf_hash(&key, *balance))
+            })
+            .collect();
+        leaves.sort_unstable_by_key(|(key, _)| *key);
+        leaves
+    }
+}
+
+fn subtree_hash(leaves: &[(Hash, Hash)], depth: usize) -> Hash {
+    match leaves {
+        [] => EMPTY,
+        [(_, leaf)] if depth == DEPTH => *leaf,
+        _ => {
+            let (left, right) = leaves.split_at(leaves.partition_point(|(k, _)| !bit(k, depth)));
+            node_hash(
+                &subtree_hash(left, depth + 1),
+                &subtree_hash(right, depth + 1),
+            )
+        }
+    }
+}
+
+pub fn account_key(account: &str) -> Hash {
+    Sha256::digest(account.as_bytes()).into()
+}
+
+fn leaf_hash(key: &Hash, balance: u128) -> Hash {
+    if balance == 0 {
+        return EMPTY;
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    hasher.update(balance.to_le_bytes());
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    // Empty subtrees hash to EMPTY, so that proofs never need the hashes of empty levels
+    if *left == EMPTY && *right == EMPTY {
+        return EMPTY;
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Whether the path to `key` goes right at `depth`.
+fn bit(key: &Hash, depth: usize) -> bool {
+    (key[depth / 8] >> (7 - depth % 8)) & 1 == 1
+}
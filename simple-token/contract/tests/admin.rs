@@ -0,0 +1,190 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB, CAROL, FAUCET};
+use contract::{
+    hash_preimage, AdminAction, SimpleToken, SimpleTokenAction, SimpleTokenError, SimpleTokenEvent,
+};
+
+fn admin(token: &mut SimpleToken, action: AdminAction) {
+    token.admin(FAUCET, action).unwrap();
+}
+
+#[test]
+fn only_the_admin_uses_the_controls() {
+    let mut token = funded();
+
+    let err = Tx::new(ALICE, SimpleTokenAction::Admin(AdminAction::Pause))
+        .execute(&mut token)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::Unauthorized(ALICE.to_string()).to_string()
+    );
+    assert!(!token.paused);
+
+    let events = Tx::new(FAUCET, SimpleTokenAction::Admin(AdminAction::Pause))
+        .execute(&mut token)
+        .unwrap();
+    assert_eq!(events, vec![SimpleTokenEvent::Admin(AdminAction::Pause)]);
+    assert!(token.paused);
+}
+
+#[test]
+fn pause_stops_all_transfers() {
+    let mut token = funded();
+    admin(&mut token, AdminAction::Pause);
+    assert_eq!(token.transfer(ALICE, BOB, 1), Err(SimpleTokenError::Paused));
+    assert_eq!(
+        token.batch_transfer(ALICE, &[(BOB.to_string(), 1)]),
+        Err(SimpleTokenError::Paused)
+    );
+
+    admin(&mut token, AdminAction::Unpause);
+    token.transfer(ALICE, BOB, 1).unwrap();
+}
+
+#[test]
+fn frozen_account_neither_sends_nor_receives() {
+    let mut token = funded();
+    admin(
+        &mut token,
+        AdminAction::Freeze {
+            account: ALICE.to_string(),
+        },
+    );
+    let frozen = Err(SimpleTokenError::AccountFrozen(ALICE.to_string()));
+    assert_eq!(token.transfer(ALICE, BOB, 1), frozen);
+    assert_eq!(token.transfer(FAUCET, ALICE, 1), frozen);
+
+    admin(
+        &mut token,
+        AdminAction::Unfreeze {
+            account: ALICE.to_string(),
+        },
+    );
+    token.transfer(ALICE, BOB, 1).unwrap();
+}
+
+#[test]
+fn allow_list_restricts_transfers_once_enabled() {
+    let mut token = funded();
+    admin(
+        &mut token,
+        AdminAction::Allow {
+            account: ALICE.to_string(),
+        },
+    );
+    // The list has no effect until enabled
+    token.transfer(ALICE, BOB, 1).unwrap();
+
+    admin(&mut token, AdminAction::EnableAllowList);
+    assert_eq!(
+        token.transfer(ALICE, BOB, 1),
+        Err(SimpleTokenError::NotAllowed(BOB.to_string()))
+    );
+    admin(
+        &mut token,
+        AdminAction::Allow {
+            account: BOB.to_string(),
+        },
+    );
+    token.transfer(ALICE, BOB, 1).unwrap();
+
+    admin(
+        &mut token,
+        AdminAction::Disallow {
+            account: ALICE.to_string(),
+        },
+    );
+    assert_eq!(
+        token.transfer(ALICE, BOB, 1),
+        Err(SimpleTokenError::NotAllowed(ALICE.to_string()))
+    );
+    admin(&mut token, AdminAction::DisableAllowList);
+    token.transfer(ALICE, BOB, 1).unwrap();
+}
+
+#[test]
+fn controls_apply_to_approvals_and_spenders() {
+    let mut token = funded();
+    token.approve(ALICE, BOB, 50).unwrap();
+    admin(
+        &mut token,
+        AdminAction::Freeze {
+            account: BOB.to_string(),
+        },
+    );
+    let frozen = Err(SimpleTokenError::AccountFrozen(BOB.to_string()));
+
+    // A frozen spender cannot move the tokens of others to an unfrozen account
+    assert_eq!(token.transfer_from(BOB, ALICE, CAROL, 10), frozen);
+    // Nor can it get or give an allowance
+    assert_eq!(token.approve(ALICE, BOB, 60), frozen);
+    assert_eq!(token.approve(BOB, ALICE, 60), frozen);
+    assert_eq!(token.allowance(ALICE, BOB), 50);
+
+    admin(&mut token, AdminAction::EnableAllowList);
+    admin(
+        &mut token,
+        AdminAction::Allow {
+            account: ALICE.to_string(),
+        },
+    );
+    assert_eq!(
+        token.approve(ALICE, CAROL, 10),
+        Err(SimpleTokenError::NotAllowed(CAROL.to_string()))
+    );
+}
+
+#[test]
+fn controls_apply_to_mints_burns_and_refunds() {
+    let mut token = funded();
+    let lock = SimpleTokenAction::LockTransfer {
+        recipient: BOB.to_string(),
+        amount: 40,
+        hashlock: hash_preimage(b"secret"),
+        timeout: 20,
+    };
+    Tx::new(ALICE, lock).at(10).execute(&mut token).unwrap();
+    let refund = |token: &mut SimpleToken| {
+        Tx::new(ALICE, SimpleTokenAction::Refund { lock_id: 0 })
+            .at(20)
+            .execute(token)
+            .map(|_| ())
+    };
+
+    admin(&mut token, AdminAction::Pause);
+    assert_eq!(token.mint(FAUCET, ALICE, 1), Err(SimpleTokenError::Paused));
+    assert_eq!(token.burn(FAUCET, 1), Err(SimpleTokenError::Paused));
+    assert_eq!(
+        refund(&mut token),
+        Err(SimpleTokenError::Paused.to_string())
+    );
+    admin(&mut token, AdminAction::Unpause);
+
+    // A frozen account gets neither new tokens nor its locked tokens back
+    for account in [ALICE, FAUCET] {
+        admin(
+            &mut token,
+            AdminAction::Freeze {
+                account: account.to_string(),
+            },
+        );
+    }
+    let frozen = |account: &str| Err(SimpleTokenError::AccountFrozen(account.to_string()));
+    assert_eq!(token.mint(FAUCET, BOB, 1), frozen(FAUCET));
+    assert_eq!(token.burn(FAUCET, 1), frozen(FAUCET));
+    admin(
+        &mut token,
+        AdminAction::Unfreeze {
+            account: FAUCET.to_string(),
+        },
+    );
+    assert_eq!(token.mint(FAUCET, ALICE, 1), frozen(ALICE));
+    assert_eq!(
+        refund(&mut token),
+        Err(SimpleTokenError::AccountFrozen(ALICE.to_string()).to_string())
+    );
+    assert_eq!(token.total_supply, 1000);
+    assert_eq!(token.locks.len(), 1);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB, CAROL};
+use contract::{SimpleTokenAction, SimpleTokenError, SimpleTokenEvent};
+
+#[test]
+fn transfer_from_consumes_the_allowance() {
+    let mut token = funded();
+    Tx::new(
+        ALICE,
+        SimpleTokenAction::Approve {
+            spender: BOB.to_string(),
+            amount: 60,
+        },
+    )
+    .execute(&mut token)
+    .unwrap();
+    assert_eq!(token.allowance(ALICE, BOB), 60);
+
+    let events = Tx::new(
+        BOB,
+        SimpleTokenAction::TransferFrom {
+            owner: ALICE.to_string(),
+            recipient: CAROL.to_string(),
+            amount: 40,
+        },
+    )
+    .execute(&mut token)
+    .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            SimpleTokenEvent::Transferred {
+                sender: ALICE.to_string(),
+                recipient: CAROL.to_string(),
+                amount: 40,
+            },
+            SimpleTokenEvent::Approved {
+                owner: ALICE.to_string(),
+                spender: BOB.to_string(),
+                amount: 20,
+            },
+        ]
+    );
+    assert_eq!(token.balance_of(ALICE), Ok(60));
+    assert_eq!(token.balance_of(CAROL), Ok(40));
+    assert_eq!(token.allowance(ALICE, BOB), 20);
+}
+
+#[test]
+fn transfer_from_cannot_exceed_the_allowance() {
+    let mut token = funded();
+    token.approve(ALICE, BOB, 30).unwrap();
+
+    let err = token.transfer_from(BOB, ALICE, BOB, 31).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InsufficientAllowance {
+            owner: ALICE.to_string(),
+            spender: BOB.to_string(),
+            allowance: 30,
+            amount: 31,
+        }
+    );
+
+    // Only the approved spender can use the allowance
+    let err = token.transfer_from(CAROL, ALICE, CAROL, 1).unwrap_err();
+    assert!(matches!(
+        err,
+        SimpleTokenError::InsufficientAllowance { allowance: 0, .. }
+    ));
+
+    // The allowance does not let the spender take more than the balance of the owner
+    token.approve(ALICE, BOB, 500).unwrap();
+    let err = token.transfer_from(BOB, ALICE, BOB, 101).unwrap_err();
+    assert!(matches!(
+        err,
+        SimpleTokenError::InsufficientBalance { balance: 100, .. }
+    ));
+    assert_eq!(token.allowance(ALICE, BOB), 500);
+}
+
+#[test]
+fn approve_replaces_the_allowance() {
+    let mut token = funded();
+    token.approve(ALICE, BOB, 30).unwrap();
+    token.approve(ALICE, BOB, 10).unwrap();
+    assert_eq!(token.allowance(ALICE, BOB), 10);
+
+    // A zero allowance is removed from the state
+    token.approve(ALICE, BOB, 0).unwrap();
+    assert_eq!(token.allowance(ALICE, BOB), 0);
+    assert!(token.allowances.is_empty());
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB, CAROL, FAUCET};
+use contract::{AdminAction, SimpleTokenAction, SimpleTokenError, SimpleTokenEvent};
+
+fn batch(transfers: &[(&str, u128)]) -> SimpleTokenAction {
+    SimpleTokenAction::BatchTransfer {
+        transfers: transfers
+            .iter()
+            .map(|(recipient, amount)| (recipient.to_string(), *amount))
+            .collect(),
+    }
+}
+
+#[test]
+fn batch_transfer_credits_every_recipient() {
+    let mut token = funded();
+
+    let events = Tx::new(ALICE, batch(&[(BOB, 30), (CAROL, 20), (BOB, 5)]))
+        .execute(&mut token)
+        .unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[1],
+        SimpleTokenEvent::Transferred {
+            sender: ALICE.to_string(),
+            recipient: CAROL.to_string(),
+            amount: 20,
+        }
+    );
+    assert_eq!(token.balance_of(ALICE), Ok(45));
+    assert_eq!(token.balance_of(BOB), Ok(35));
+    assert_eq!(token.balance_of(CAROL), Ok(20));
+}
+
+#[test]
+fn batch_transfer_is_all_or_nothing() {
+    let mut token = funded();
+
+    // Each transfer fits in the balance, but not all of them
+    let err = token
+        .batch_transfer(ALICE, &[(BOB.to_string(), 60), (CAROL.to_string(), 60)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InsufficientBalance {
+            account: ALICE.to_string(),
+            balance: 100,
+            amount: 120,
+        }
+    );
+
+    // A single disallowed recipient fails the whole batch
+    token
+        .admin(
+            FAUCET,
+            AdminAction::Freeze {
+                account: CAROL.to_string(),
+            },
+        )
+        .unwrap();
+    let err = token
+        .batch_transfer(ALICE, &[(BOB.to_string(), 10), (CAROL.to_string(), 10)])
+        .unwrap_err();
+    assert_eq!(err, SimpleTokenError::AccountFrozen(CAROL.to_string()));
+
+    // Amounts summing past u128 are rejected before anything is debited
+    let err = token
+        .batch_transfer(ALICE, &[(BOB.to_string(), u128::MAX), (BOB.to_string(), 1)])
+        .unwrap_err();
+    assert_eq!(err, SimpleTokenError::Overflow);
+
+    assert_eq!(token.balance_of(ALICE), Ok(100));
+    assert!(token.balance_of(BOB).is_err());
+    assert!(token.balance_of(CAROL).is_err());
+    token.check_supply().unwrap();
+}
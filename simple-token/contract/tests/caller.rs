@@ -0,0 +1,87 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB, CONTRACT_NAME};
+use contract::{SimpleToken, SimpleTokenAction, SimpleTokenError};
+use sdk::{Blob, BlobData, BlobIndex, ContractAction};
+
+const APP: &str = "ticket_app";
+
+/// Token where the contract-owned account of `ticket_app` holds 50.
+fn token() -> SimpleToken {
+    let mut token = funded();
+    token.transfer(ALICE, APP, 50).unwrap();
+    token
+}
+
+fn pay_bob(amount: u128) -> SimpleTokenAction {
+    SimpleTokenAction::Transfer {
+        recipient: BOB.to_string(),
+        amount,
+    }
+}
+
+/// Blob of the app, calling the token blobs at `callees`.
+fn app_blob(callees: Vec<BlobIndex>) -> Blob {
+    Blob {
+        contract_name: APP.into(),
+        data: BlobData::from(sdk::StructuredBlobData {
+            caller: None,
+            callees: Some(callees),
+            parameters: (),
+        }),
+    }
+}
+
+#[test]
+fn caller_blob_spends_the_account_of_its_contract() {
+    let mut token = token();
+    let tx = Tx {
+        identity: ALICE.to_string(),
+        block_height: None,
+        blobs: vec![
+            app_blob(vec![BlobIndex(1)]),
+            pay_bob(30).as_blob(CONTRACT_NAME.into(), Some(BlobIndex(0)), None),
+        ],
+        private_input: vec![],
+    };
+
+    tx.execute_blob(&mut token, 1).unwrap();
+    assert_eq!(token.balance_of(APP), Ok(20));
+    assert_eq!(token.balance_of(BOB), Ok(30));
+    // The identity of the transaction is not debited
+    assert_eq!(token.balance_of(ALICE), Ok(50));
+}
+
+#[test]
+fn caller_must_list_the_blob_as_callee() {
+    let mut token = token();
+    let tx = Tx {
+        identity: ALICE.to_string(),
+        block_height: None,
+        blobs: vec![
+            app_blob(vec![]),
+            pay_bob(30).as_blob(CONTRACT_NAME.into(), Some(BlobIndex(0)), None),
+        ],
+        private_input: vec![],
+    };
+
+    assert!(tx.execute_blob(&mut token, 1).is_err());
+    assert_eq!(token.balance_of(APP), Ok(50));
+}
+
+#[test]
+fn contract_account_cannot_be_the_identity() {
+    let mut token = token();
+
+    // Whoever sends a transaction as `ticket_app` does not own its account
+    let err = Tx::new(APP, pay_bob(30)).execute(&mut token).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::Unauthorized(APP.to_string()).to_string()
+    );
+    assert_eq!(token.balance_of(APP), Ok(50));
+
+    // Accounts of identities are spent by the identity of the transaction
+    Tx::new(ALICE, pay_bob(30)).execute(&mut token).unwrap();
+    assert_eq!(token.balance_of(BOB), Ok(30));
+}
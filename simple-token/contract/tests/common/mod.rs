@@ -0,0 +1,104 @@
+//! Helpers shared by the tests of the contract. Each test file uses only some of them.
+#![allow(dead_code)]
+
+use contract::{
+    SimpleToken, SimpleTokenAction, SimpleTokenEvent, SimpleTokenOutput, TokenMetadata,
+};
+use sdk::{
+    Blob, BlobIndex, BlockHeight, ContractAction, ContractInput, HyleContract, TxContext, TxHash,
+};
+
+pub const CONTRACT_NAME: &str = "simple_token";
+/// Account holding the initial supply, which is also the minter and the admin of the token.
+pub const FAUCET: &str = "faucet.simple_token";
+pub const ALICE: &str = "alice.simple_token";
+pub const BOB: &str = "bob.simple_token";
+pub const CAROL: &str = "carol.simple_token";
+
+/// Token with 1000 units on the faucet, capped at 2000, with 2 decimals.
+pub fn token() -> SimpleToken {
+    let metadata = TokenMetadata {
+        name: "Simple Token".to_string(),
+        symbol: "SMP".to_string(),
+        decimals: 2,
+    };
+    SimpleToken::new(
+        1000,
+        FAUCET.to_string(),
+        FAUCET.to_string(),
+        Some(2000),
+        metadata,
+        FAUCET.to_string(),
+    )
+}
+
+/// Token where alice holds 100 units taken from the faucet.
+pub fn funded() -> SimpleToken {
+    let mut token = token();
+    token.transfer(FAUCET, ALICE, 100).unwrap();
+    token
+}
+
+/// Transaction whose blob at index 0 is an action of the token.
+pub struct Tx {
+    pub identity: String,
+    pub block_height: Option<u64>,
+    pub blobs: Vec<Blob>,
+    pub private_input: Vec<u8>,
+}
+
+impl Tx {
+    pub fn new(identity: &str, action: SimpleTokenAction) -> Self {
+        Tx {
+            identity: identity.to_string(),
+            block_height: None,
+            blobs: vec![action.as_blob(CONTRACT_NAME.into(), None, None)],
+            private_input: vec![],
+        }
+    }
+
+    /// Includes the transaction in the block `block_height`.
+    pub fn at(mut self, block_height: u64) -> Self {
+        self.block_height = Some(block_height);
+        self
+    }
+
+    pub fn with_private_input(mut self, private_input: &[u8]) -> Self {
+        self.private_input = private_input.to_vec();
+        self
+    }
+
+    /// Input of the blob at `index`, for a contract in the serialized `state`.
+    pub fn input(&self, state: Vec<u8>, index: usize) -> ContractInput {
+        ContractInput {
+            state,
+            identity: self.identity.as_str().into(),
+            index: BlobIndex(index),
+            blobs: self.blobs.clone(),
+            tx_hash: TxHash("01".to_string()),
+            tx_ctx: self.block_height.map(|block_height| TxContext {
+                block_height: BlockHeight(block_height),
+                ..Default::default()
+            }),
+            private_input: self.private_input.clone(),
+        }
+    }
+
+    /// Executes the blob at `index`. A failed transaction does not change the state.
+    pub fn execute_blob(
+        &self,
+        token: &mut SimpleToken,
+        index: usize,
+    ) -> Result<Vec<SimpleTokenEvent>, String> {
+        let input = self.input(token.as_bytes().unwrap(), index);
+        let mut next_token = token.clone();
+        let (output, _, _) = next_token.execute(&input)?;
+        *token = next_token;
+        Ok(SimpleTokenOutput::decode(&output)?.events().to_vec())
+    }
+
+    /// Executes the token action.
+    pub fn execute(&self, token: &mut SimpleToken) -> Result<Vec<SimpleTokenEvent>, String> {
+        self.execute_blob(token, 0)
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{token, Tx, ALICE, BOB, FAUCET};
+use contract::{AdminAction, SimpleToken, SimpleTokenAction, SimpleTokenError};
+
+fn drip(token: &mut SimpleToken, identity: &str, block_height: u64) -> Result<(), String> {
+    Tx::new(identity, SimpleTokenAction::Drip {})
+        .at(block_height)
+        .execute(token)
+        .map(|_| ())
+}
+
+/// Token letting anyone claim 10 from the faucet, once every 5 blocks.
+fn dripping() -> SimpleToken {
+    let mut token = token();
+    let action = SimpleTokenAction::Admin(AdminAction::SetDrip {
+        amount: 10,
+        cooldown: 5,
+    });
+    Tx::new(FAUCET, action).execute(&mut token).unwrap();
+    token
+}
+
+#[test]
+fn drip_is_disabled_by_default() {
+    let mut token = token();
+    let err = drip(&mut token, ALICE, 1).unwrap_err();
+    assert_eq!(err, SimpleTokenError::DripDisabled.to_string());
+
+    // Only the admin enables it
+    let action = SimpleTokenAction::Admin(AdminAction::SetDrip {
+        amount: 10,
+        cooldown: 5,
+    });
+    let err = Tx::new(ALICE, action).execute(&mut token).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::Unauthorized(ALICE.to_string()).to_string()
+    );
+}
+
+#[test]
+fn drip_is_rate_limited_per_account() {
+    let mut token = dripping();
+    drip(&mut token, ALICE, 10).unwrap();
+    assert_eq!(token.balance_of(ALICE), Ok(10));
+    assert_eq!(token.balance_of(FAUCET), Ok(990));
+
+    let err = drip(&mut token, ALICE, 14).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::DripCooldown {
+            account: ALICE.to_string(),
+            available_at: 15,
+        }
+        .to_string()
+    );
+    // The cooldown of alice does not apply to bob
+    drip(&mut token, BOB, 14).unwrap();
+
+    drip(&mut token, ALICE, 15).unwrap();
+    assert_eq!(token.balance_of(ALICE), Ok(20));
+}
+
+#[test]
+fn drip_is_limited_by_the_faucet_balance() {
+    let mut token = dripping();
+    token.transfer(FAUCET, BOB, 995).unwrap();
+
+    let err = drip(&mut token, ALICE, 10).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InsufficientBalance {
+            account: FAUCET.to_string(),
+            balance: 5,
+            amount: 10,
+        }
+        .to_string()
+    );
+    // A failed drip does not start the cooldown
+    assert!(token.last_drips.is_empty());
+}
+
+#[test]
+fn drip_can_be_disabled() {
+    let mut token = dripping();
+    let action = SimpleTokenAction::Admin(AdminAction::DisableDrip);
+    Tx::new(FAUCET, action).execute(&mut token).unwrap();
+
+    let err = drip(&mut token, ALICE, 10).unwrap_err();
+    assert_eq!(err, SimpleTokenError::DripDisabled.to_string());
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB, CAROL};
+use contract::{SimpleTokenAction, SimpleTokenError};
+
+#[test]
+fn functions_return_typed_errors() {
+    let mut token = funded();
+
+    assert_eq!(
+        token.balance_of(CAROL),
+        Err(SimpleTokenError::AccountNotFound(CAROL.to_string()))
+    );
+    assert_eq!(
+        token.transfer(CAROL, ALICE, 1),
+        Err(SimpleTokenError::AccountNotFound(CAROL.to_string()))
+    );
+    assert_eq!(
+        token.transfer(ALICE, BOB, 101),
+        Err(SimpleTokenError::InsufficientBalance {
+            account: ALICE.to_string(),
+            balance: 100,
+            amount: 101,
+        })
+    );
+
+    // A failed function leaves the balances untouched
+    assert_eq!(token.balance_of(ALICE), Ok(100));
+    assert_eq!(
+        token.balance_of(BOB),
+        Err(SimpleTokenError::AccountNotFound(BOB.to_string()))
+    );
+}
+
+#[test]
+fn execute_returns_the_error_message() {
+    let mut token = funded();
+    let transfer = SimpleTokenAction::Transfer {
+        recipient: BOB.to_string(),
+        amount: 101,
+    };
+
+    let err = Tx::new(ALICE, transfer).execute(&mut token).unwrap_err();
+    assert_eq!(
+        err,
+        "Insufficient balance: alice.simple_token has 100 but needs 101"
+    );
+}
+
+#[test]
+fn credit_overflow_is_an_error() {
+    let mut token = funded();
+    token.balances.insert(BOB.to_string(), u128::MAX);
+
+    assert_eq!(
+        token.transfer(ALICE, BOB, 1),
+        Err(SimpleTokenError::Overflow)
+    );
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB};
+use contract::{
+    hash_preimage, HashLock, SimpleToken, SimpleTokenAction, SimpleTokenError, SimpleTokenEvent,
+};
+
+const PREIMAGE: &[u8] = b"secret";
+
+/// Locks 40 of alice for bob at block 10, until block 20.
+fn lock(token: &mut SimpleToken) -> u64 {
+    let action = SimpleTokenAction::LockTransfer {
+        recipient: BOB.to_string(),
+        amount: 40,
+        hashlock: hash_preimage(PREIMAGE),
+        timeout: 20,
+    };
+    match Tx::new(ALICE, action).at(10).execute(token).unwrap()[..] {
+        [SimpleTokenEvent::Locked { lock_id, .. }] => lock_id,
+        ref events => panic!("Unexpected events {events:?}"),
+    }
+}
+
+fn claim(
+    token: &mut SimpleToken,
+    lock_id: u64,
+    block_height: u64,
+    preimage: &[u8],
+) -> Result<Vec<SimpleTokenEvent>, String> {
+    Tx::new(BOB, SimpleTokenAction::Claim { lock_id })
+        .at(block_height)
+        .with_private_input(preimage)
+        .execute(token)
+}
+
+fn refund(
+    token: &mut SimpleToken,
+    lock_id: u64,
+    block_height: u64,
+) -> Result<Vec<SimpleTokenEvent>, String> {
+    Tx::new(ALICE, SimpleTokenAction::Refund { lock_id })
+        .at(block_height)
+        .execute(token)
+}
+
+#[test]
+fn locked_tokens_count_in_the_supply() {
+    let mut token = funded();
+    let lock_id = lock(&mut token);
+    assert_eq!(token.balance_of(ALICE), Ok(60));
+    assert_eq!(
+        token.locks[&lock_id],
+        HashLock {
+            sender: ALICE.to_string(),
+            recipient: BOB.to_string(),
+            amount: 40,
+            hashlock: hash_preimage(PREIMAGE),
+            timeout: 20,
+        }
+    );
+    token.check_supply().unwrap();
+    assert_eq!(lock(&mut token), lock_id + 1);
+}
+
+#[test]
+fn claim_is_possible_until_the_block_before_the_timeout() {
+    let mut token = funded();
+    let lock_id = lock(&mut token);
+
+    let err = claim(&mut token, lock_id, 19, b"guess").unwrap_err();
+    assert_eq!(err, SimpleTokenError::InvalidPreimage(lock_id).to_string());
+    // Before the timeout, the sender cannot take the tokens back
+    let err = refund(&mut token, lock_id, 19).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::LockNotExpired {
+            lock_id,
+            timeout: 20
+        }
+        .to_string()
+    );
+
+    let events = claim(&mut token, lock_id, 19, PREIMAGE).unwrap();
+    assert_eq!(
+        events[0],
+        SimpleTokenEvent::Claimed {
+            lock_id,
+            preimage: PREIMAGE.to_vec(),
+        }
+    );
+    assert_eq!(token.balance_of(BOB), Ok(40));
+    assert!(token.locks.is_empty());
+
+    // A lock is settled once
+    let err = refund(&mut token, lock_id, 20).unwrap_err();
+    assert_eq!(err, SimpleTokenError::LockNotFound(lock_id).to_string());
+}
+
+#[test]
+fn refund_is_possible_from_the_timeout() {
+    let mut token = funded();
+    let lock_id = lock(&mut token);
+
+    // At the timeout, the preimage does not unlock the tokens anymore
+    let err = claim(&mut token, lock_id, 20, PREIMAGE).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::LockExpired {
+            lock_id,
+            timeout: 20
+        }
+        .to_string()
+    );
+
+    refund(&mut token, lock_id, 20).unwrap();
+    assert_eq!(token.balance_of(ALICE), Ok(100));
+    let err = claim(&mut token, lock_id, 19, PREIMAGE).unwrap_err();
+    assert_eq!(err, SimpleTokenError::LockNotFound(lock_id).to_string());
+}
+
+#[test]
+fn timeout_must_be_in_the_future() {
+    let mut token = funded();
+    let action = SimpleTokenAction::LockTransfer {
+        recipient: BOB.to_string(),
+        amount: 40,
+        hashlock: hash_preimage(PREIMAGE),
+        timeout: 10,
+    };
+
+    let err = Tx::new(ALICE, action)
+        .at(10)
+        .execute(&mut token)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InvalidTimeout {
+            timeout: 10,
+            block_height: 10,
+        }
+        .to_string()
+    );
+    assert_eq!(token.next_lock_id, 0);
+}
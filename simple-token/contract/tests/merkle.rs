@@ -0,0 +1,139 @@
+mod common;
+
+use common::{Tx, ALICE, BOB, CAROL};
+use contract::merkle::{BalanceProof, BalanceTree, MerkleToken, EMPTY};
+use contract::{SimpleTokenAction, SimpleTokenError};
+use sdk::HyleContract;
+
+/// Tree where alice holds 100 and carol 50.
+fn tree() -> BalanceTree {
+    let mut tree = BalanceTree::default();
+    tree.set_balance(ALICE, 100);
+    tree.set_balance(CAROL, 50);
+    tree
+}
+
+/// Transfers `amount` from alice to bob, with the proofs the host builds.
+fn transfer(
+    token: &mut MerkleToken,
+    tree: &mut BalanceTree,
+    amount: u128,
+) -> Result<(), SimpleTokenError> {
+    let sender_proof = tree.proof(ALICE);
+    let mut next_tree = tree.clone();
+    next_tree.set_balance(ALICE, tree.balance_of(ALICE).saturating_sub(amount));
+    let recipient_proof = next_tree.proof(BOB);
+    token.transfer(ALICE, BOB, amount, vec![sender_proof, recipient_proof])?;
+    next_tree.set_balance(BOB, tree.balance_of(BOB) + amount);
+    *tree = next_tree;
+    Ok(())
+}
+
+#[test]
+fn transfer_updates_the_root() {
+    let mut tree = tree();
+    let mut token = MerkleToken::new(tree.root());
+
+    transfer(&mut token, &mut tree, 30).unwrap();
+    assert_eq!(token.root, tree.root());
+    assert_eq!(tree.balance_of(ALICE), 70);
+    assert_eq!(tree.balance_of(BOB), 30);
+
+    // Emptying an account removes its leaf
+    transfer(&mut token, &mut tree, 70).unwrap();
+    assert_eq!(token.root, tree.root());
+    let mut expected = BalanceTree::default();
+    expected.set_balance(BOB, 100);
+    expected.set_balance(CAROL, 50);
+    assert_eq!(token.root, expected.root());
+}
+
+#[test]
+fn empty_tree_has_the_empty_root() {
+    assert_eq!(BalanceTree::default().root(), EMPTY);
+}
+
+#[test]
+fn forged_balance_is_rejected() {
+    let tree = tree();
+    let mut token = MerkleToken::new(tree.root());
+
+    // Alice claims a larger balance than the one committed to
+    let mut forged = tree.proof(ALICE);
+    forged.balance = 1000;
+    let err = token
+        .transfer(ALICE, BOB, 500, vec![forged, tree.proof(BOB)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InvalidProof(format!("Invalid balance proof for {ALICE}"))
+    );
+    assert_eq!(token.root, tree.root());
+}
+
+#[test]
+fn proofs_must_match_the_accounts() {
+    let tree = tree();
+    let mut token = MerkleToken::new(tree.root());
+
+    let err = token
+        .transfer(ALICE, BOB, 10, vec![tree.proof(CAROL)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InvalidProof(format!(
+            "Expected balance proof for {ALICE} but got {CAROL}"
+        ))
+    );
+
+    let err = token
+        .transfer(ALICE, BOB, 10, vec![tree.proof(ALICE)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InvalidProof(format!("Missing balance proof for {BOB}"))
+    );
+
+    // The recipient proof must be built against the root updated by the debit
+    let mut token = MerkleToken::new(tree.root());
+    let err = token
+        .transfer(ALICE, BOB, 10, vec![tree.proof(ALICE), tree.proof(BOB)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InvalidProof(format!("Invalid balance proof for {BOB}"))
+    );
+}
+
+#[test]
+fn transfer_cannot_exceed_the_proven_balance() {
+    let mut tree = tree();
+    let mut token = MerkleToken::new(tree.root());
+
+    let err = transfer(&mut token, &mut tree, 101).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InsufficientBalance {
+            account: ALICE.to_string(),
+            balance: 100,
+            amount: 101,
+        }
+    );
+}
+
+#[test]
+fn only_transfers_are_supported() {
+    let tree = tree();
+    let mut token = MerkleToken::new(tree.root());
+
+    let proofs: Vec<BalanceProof> = vec![tree.proof(ALICE)];
+    let input = Tx::new(ALICE, SimpleTokenAction::Burn { amount: 10 })
+        .with_private_input(&borsh::to_vec(&proofs).unwrap())
+        .input(borsh::to_vec(&token).unwrap(), 0);
+    let err = token.execute(&input).unwrap_err();
+    assert_eq!(
+        err,
+        "Burn is not supported in Merkle mode, only Transfer is"
+    );
+    assert_eq!(token.root, tree.root());
+}
@@ -0,0 +1,55 @@
+use contract::TokenMetadata;
+
+fn metadata(decimals: u8) -> TokenMetadata {
+    TokenMetadata {
+        name: "Simple Token".to_string(),
+        symbol: "SMP".to_string(),
+        decimals,
+    }
+}
+
+#[test]
+fn amounts_are_parsed_in_base_units() {
+    let cents = metadata(2);
+    assert_eq!(cents.parse_amount("12.5"), Ok(1250));
+    assert_eq!(cents.parse_amount("12.05"), Ok(1205));
+    assert_eq!(cents.parse_amount("12"), Ok(1200));
+    assert_eq!(cents.parse_amount("12."), Ok(1200));
+    assert_eq!(cents.parse_amount(".5"), Ok(50));
+    assert_eq!(cents.parse_amount("0"), Ok(0));
+}
+
+#[test]
+fn invalid_amounts_are_rejected() {
+    let cents = metadata(2);
+    for amount in ["", ".", "1.234", "-1", "1e3", "1.2.3", " 1", "1,5"] {
+        assert_eq!(
+            cents.parse_amount(amount),
+            Err(format!(
+                "Invalid amount {amount} for a token with 2 decimals"
+            )),
+            "{amount}"
+        );
+    }
+
+    // Amounts that do not fit in u128 once scaled
+    assert!(cents.parse_amount(&u128::MAX.to_string()).is_err());
+    // A token without decimals only takes integers
+    assert!(metadata(0).parse_amount("1.5").is_err());
+}
+
+#[test]
+fn amounts_are_formatted_without_trailing_zeros() {
+    let cents = metadata(2);
+    assert_eq!(cents.format_amount(1250), "12.5");
+    assert_eq!(cents.format_amount(1205), "12.05");
+    assert_eq!(cents.format_amount(1200), "12");
+    assert_eq!(cents.format_amount(5), "0.05");
+    assert_eq!(metadata(0).format_amount(1250), "1250");
+
+    // Formatting and parsing are inverses
+    for amount in [0, 1, 99, 100, 1234567, u128::MAX] {
+        let formatted = cents.format_amount(amount);
+        assert_eq!(cents.parse_amount(&formatted), Ok(amount));
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB};
+use contract::{SimpleTokenAction, SimpleTokenEvent, SimpleTokenOutput};
+
+#[test]
+fn output_decodes_to_the_events_of_the_action() {
+    let mut token = funded();
+    let action = SimpleTokenAction::BatchTransfer {
+        transfers: vec![(BOB.to_string(), 10), (ALICE.to_string(), 5)],
+    };
+
+    let events = Tx::new(ALICE, action).execute(&mut token).unwrap();
+    assert_eq!(
+        events,
+        vec![
+            SimpleTokenEvent::Transferred {
+                sender: ALICE.to_string(),
+                recipient: BOB.to_string(),
+                amount: 10,
+            },
+            SimpleTokenEvent::Transferred {
+                sender: ALICE.to_string(),
+                recipient: ALICE.to_string(),
+                amount: 5,
+            },
+        ]
+    );
+}
+
+#[test]
+fn output_is_versioned_borsh_in_hex() {
+    let output = SimpleTokenOutput::V1(vec![SimpleTokenEvent::Burned {
+        owner: ALICE.to_string(),
+        amount: 3,
+    }]);
+    let encoded = output.encode();
+
+    // The first byte is the version: V1 is the variant 0
+    assert!(encoded.starts_with("00"));
+    assert_eq!(
+        hex::decode(&encoded).unwrap(),
+        borsh::to_vec(&output).unwrap()
+    );
+    assert_eq!(SimpleTokenOutput::decode(&encoded), Ok(output));
+
+    assert!(SimpleTokenOutput::decode("not hex").is_err());
+    // A version this contract does not know is rejected
+    assert!(SimpleTokenOutput::decode("01").is_err());
+}
+
+#[test]
+fn events_are_displayed_for_the_hosts() {
+    let event = SimpleTokenEvent::Transferred {
+        sender: ALICE.to_string(),
+        recipient: BOB.to_string(),
+        amount: 10,
+    };
+    assert_eq!(
+        event.to_string(),
+        "Transferred 10 from alice.simple_token to bob.simple_token"
+    );
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB, CAROL, CONTRACT_NAME};
+use contract::{PermitPayload, SimpleToken, SimpleTokenAction, SimpleTokenError};
+use ed25519_dalek::{Signer, SigningKey};
+
+/// Key signing the permits of alice.
+fn key() -> SigningKey {
+    SigningKey::from_bytes(&[7; 32])
+}
+
+fn payload(nonce: u64, deadline: u64) -> PermitPayload {
+    PermitPayload {
+        contract: CONTRACT_NAME.to_string(),
+        owner: ALICE.to_string(),
+        spender: BOB.to_string(),
+        amount: 50,
+        nonce,
+        deadline,
+    }
+}
+
+/// Token where alice set the public key of `key`.
+fn with_key() -> SimpleToken {
+    let mut token = funded();
+    let action = SimpleTokenAction::SetPermitKey {
+        public_key: key().verifying_key().to_bytes(),
+    };
+    Tx::new(ALICE, action).execute(&mut token).unwrap();
+    token
+}
+
+/// Sends the permit of `payload` signed by `key`, as carol, at `block_height`.
+fn permit(
+    token: &mut SimpleToken,
+    key: &SigningKey,
+    payload: PermitPayload,
+    block_height: u64,
+) -> Result<(), String> {
+    let signature = key.sign(&payload.signing_bytes()).to_bytes().to_vec();
+    let action = SimpleTokenAction::Permit {
+        owner: payload.owner,
+        spender: payload.spender,
+        amount: payload.amount,
+        nonce: payload.nonce,
+        deadline: payload.deadline,
+        signature,
+    };
+    Tx::new(CAROL, action)
+        .at(block_height)
+        .execute(token)
+        .map(|_| ())
+}
+
+#[test]
+fn permit_approves_on_behalf_of_the_owner() {
+    let mut token = with_key();
+    permit(&mut token, &key(), payload(0, 10), 10).unwrap();
+    assert_eq!(token.allowance(ALICE, BOB), 50);
+    assert_eq!(token.permit_nonce(ALICE), 1);
+
+    // The nonce is consumed, the same permit cannot be replayed
+    let err = permit(&mut token, &key(), payload(0, 10), 10).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::InvalidNonce {
+            owner: ALICE.to_string(),
+            expected: 1,
+            nonce: 0,
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn permit_is_valid_until_its_deadline() {
+    let mut token = with_key();
+    let err = permit(&mut token, &key(), payload(0, 10), 11).unwrap_err();
+    assert_eq!(err, SimpleTokenError::PermitExpired(10).to_string());
+}
+
+#[test]
+fn permit_must_be_signed_by_the_key_of_the_owner() {
+    let mut token = funded();
+    let err = permit(&mut token, &key(), payload(0, 10), 10).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::PermitKeyNotSet(ALICE.to_string()).to_string()
+    );
+
+    let mut token = with_key();
+    let mallory = SigningKey::from_bytes(&[6; 32]);
+    let err = permit(&mut token, &mallory, payload(0, 10), 10).unwrap_err();
+    assert_eq!(err, SimpleTokenError::InvalidSignature.to_string());
+
+    // The signature covers the contract, so a permit for another token is rejected
+    let mut other = payload(0, 10);
+    other.contract = "other_token".to_string();
+    let err = permit(&mut token, &key(), other, 10).unwrap_err();
+    assert_eq!(err, SimpleTokenError::InvalidSignature.to_string());
+    assert_eq!(token.allowance(ALICE, BOB), 0);
+}
+
+#[test]
+fn permit_key_must_be_a_valid_public_key() {
+    let mut token = funded();
+    // Not the encoding of a point of the curve
+    let mut public_key = [0; 32];
+    public_key[0] = 2;
+    let err = Tx::new(ALICE, SimpleTokenAction::SetPermitKey { public_key })
+        .execute(&mut token)
+        .unwrap_err();
+    assert_eq!(err, SimpleTokenError::InvalidPublicKey.to_string());
+    assert!(token.permit_keys.is_empty());
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{funded, Tx, ALICE, BOB, CAROL, FAUCET};
+use contract::{SimpleToken, SimpleTokenAction, SimpleTokenError, SimpleTokenEvent};
+
+fn snapshot(token: &mut SimpleToken) -> u64 {
+    match Tx::new(FAUCET, SimpleTokenAction::Snapshot {})
+        .execute(token)
+        .unwrap()[..]
+    {
+        [SimpleTokenEvent::Snapshot { id }] => id,
+        ref events => panic!("Unexpected events {events:?}"),
+    }
+}
+
+#[test]
+fn only_the_admin_takes_snapshots() {
+    let mut token = funded();
+    let err = Tx::new(ALICE, SimpleTokenAction::Snapshot {})
+        .execute(&mut token)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::Unauthorized(ALICE.to_string()).to_string()
+    );
+    assert_eq!(token.snapshot_id, 0);
+}
+
+#[test]
+fn balance_at_reads_each_snapshot() {
+    let mut token = funded();
+    assert_eq!(
+        token.balance_at(ALICE, 1),
+        Err(SimpleTokenError::SnapshotNotFound(1))
+    );
+
+    // Snapshot 1: alice 100, bob 0
+    assert_eq!(snapshot(&mut token), 1);
+    token.transfer(ALICE, BOB, 10).unwrap();
+    token.transfer(ALICE, BOB, 10).unwrap();
+
+    // Snapshots 2 and 3 are taken without any change in between: alice 80, bob 20
+    assert_eq!(snapshot(&mut token), 2);
+    assert_eq!(snapshot(&mut token), 3);
+    token.transfer(BOB, CAROL, 5).unwrap();
+
+    // Snapshot 4: alice 80, bob 15, carol 5
+    assert_eq!(snapshot(&mut token), 4);
+    token.transfer(ALICE, CAROL, 80).unwrap();
+
+    for (account, balances) in [
+        (ALICE, [100, 80, 80, 80]),
+        (BOB, [0, 20, 20, 15]),
+        (CAROL, [0, 0, 0, 5]),
+        (FAUCET, [900, 900, 900, 900]),
+    ] {
+        for (id, balance) in (1..).zip(balances) {
+            assert_eq!(
+                token.balance_at(account, id),
+                Ok(balance),
+                "{account} at {id}"
+            );
+        }
+    }
+
+    // Only the snapshots taken can be read
+    assert_eq!(
+        token.balance_at(ALICE, 5),
+        Err(SimpleTokenError::SnapshotNotFound(5))
+    );
+    assert_eq!(
+        token.balance_at(ALICE, 0),
+        Err(SimpleTokenError::SnapshotNotFound(0))
+    );
+
+    // The current balances include the changes after the last snapshot
+    assert_eq!(token.balance_of(ALICE), Ok(0));
+    assert_eq!(token.balance_of(CAROL), Ok(85));
+}
+
+#[test]
+fn only_the_first_change_after_a_snapshot_is_recorded() {
+    let mut token = funded();
+    snapshot(&mut token);
+    for _ in 0..5 {
+        token.transfer(ALICE, BOB, 1).unwrap();
+    }
+    assert_eq!(token.checkpoints[ALICE], vec![(1, 100)]);
+    assert_eq!(token.checkpoints[BOB], vec![(1, 0)]);
+
+    snapshot(&mut token);
+    token.transfer(ALICE, BOB, 1).unwrap();
+    assert_eq!(token.checkpoints[ALICE], vec![(1, 100), (2, 95)]);
+}
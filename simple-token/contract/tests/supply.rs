@@ -0,0 +1,87 @@
+mod common;
+
+use common::{funded, token, Tx, ALICE, FAUCET};
+use contract::{SimpleTokenAction, SimpleTokenError};
+
+fn mint(amount: u128) -> SimpleTokenAction {
+    SimpleTokenAction::Mint {
+        recipient: ALICE.to_string(),
+        amount,
+    }
+}
+
+#[test]
+fn only_the_minter_mints() {
+    let mut token = token();
+
+    let err = Tx::new(ALICE, mint(10)).execute(&mut token).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::Unauthorized(ALICE.to_string()).to_string()
+    );
+
+    Tx::new(FAUCET, mint(10)).execute(&mut token).unwrap();
+    assert_eq!(token.balance_of(ALICE), Ok(10));
+    assert_eq!(token.total_supply, 1010);
+}
+
+#[test]
+fn mint_is_capped_by_the_max_supply() {
+    let mut token = token();
+
+    // The cap is reached exactly, but cannot be exceeded
+    Tx::new(FAUCET, mint(1000)).execute(&mut token).unwrap();
+    let err = token.mint(FAUCET, ALICE, 1).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::MaxSupplyExceeded {
+            max_supply: 2000,
+            total_supply: 2001,
+        }
+    );
+
+    // Without a cap, the supply is still bounded by u128
+    token.max_supply = None;
+    let err = token.mint(FAUCET, ALICE, u128::MAX).unwrap_err();
+    assert_eq!(err, SimpleTokenError::Overflow);
+    assert_eq!(token.total_supply, 2000);
+    token.check_supply().unwrap();
+}
+
+#[test]
+fn only_the_minter_burns_its_own_tokens() {
+    let mut token = funded();
+
+    let err = Tx::new(ALICE, SimpleTokenAction::Burn { amount: 10 })
+        .execute(&mut token)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::Unauthorized(ALICE.to_string()).to_string()
+    );
+
+    Tx::new(FAUCET, SimpleTokenAction::Burn { amount: 900 })
+        .execute(&mut token)
+        .unwrap();
+    assert_eq!(token.balance_of(FAUCET), Ok(0));
+    assert_eq!(token.total_supply, 100);
+
+    let err = token.burn(FAUCET, 1).unwrap_err();
+    assert!(matches!(
+        err,
+        SimpleTokenError::InsufficientBalance { balance: 0, .. }
+    ));
+}
+
+#[test]
+fn supply_must_match_the_balances() {
+    let mut token = token();
+    token.balances.insert(ALICE.to_string(), 1);
+    assert_eq!(
+        token.check_supply(),
+        Err(SimpleTokenError::SupplyMismatch {
+            total_supply: 1000,
+            balances: 1001,
+        })
+    );
+}
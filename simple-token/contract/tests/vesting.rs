@@ -0,0 +1,158 @@
+mod common;
+
+use common::{token, Tx, ALICE, BOB, FAUCET};
+use contract::{SimpleToken, SimpleTokenAction, SimpleTokenError, VestingSchedule};
+
+/// Vests 100 to alice from block 10, with a cliff of 20 blocks over 100 blocks.
+fn vest(token: &mut SimpleToken) {
+    let action = SimpleTokenAction::CreateVesting {
+        beneficiary: ALICE.to_string(),
+        amount: 100,
+        cliff: 20,
+        duration: 100,
+    };
+    Tx::new(FAUCET, action).at(10).execute(token).unwrap();
+}
+
+fn transfer(token: &mut SimpleToken, block_height: u64, amount: u128) -> Result<(), String> {
+    let action = SimpleTokenAction::Transfer {
+        recipient: BOB.to_string(),
+        amount,
+    };
+    Tx::new(ALICE, action)
+        .at(block_height)
+        .execute(token)
+        .map(|_| ())
+}
+
+#[test]
+fn only_the_admin_creates_vesting() {
+    let mut token = token();
+    token.transfer(FAUCET, BOB, 100).unwrap();
+    let action = SimpleTokenAction::CreateVesting {
+        beneficiary: ALICE.to_string(),
+        amount: 100,
+        cliff: 0,
+        duration: 10,
+    };
+
+    // Anyone else could lock the tokens sent to the beneficiary
+    let err = Tx::new(BOB, action).at(10).execute(&mut token).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::Unauthorized(BOB.to_string()).to_string()
+    );
+}
+
+#[test]
+fn vested_tokens_are_released_linearly_after_the_cliff() {
+    let mut token = token();
+    vest(&mut token);
+    assert_eq!(
+        token.vesting[ALICE],
+        VestingSchedule {
+            total: 100,
+            start: 10,
+            cliff: 20,
+            duration: 100,
+        }
+    );
+
+    // Nothing is released before the cliff
+    let err = transfer(&mut token, 29, 1).unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::VestingLocked {
+            account: ALICE.to_string(),
+            locked: 100,
+            amount: 1,
+        }
+        .to_string()
+    );
+
+    // At the cliff, the tokens vested since the start are released at once
+    transfer(&mut token, 30, 20).unwrap();
+    assert!(transfer(&mut token, 30, 1).is_err());
+
+    // The rest is released block by block, until the end of the schedule
+    transfer(&mut token, 109, 79).unwrap();
+    assert!(transfer(&mut token, 109, 1).is_err());
+    transfer(&mut token, 110, 1).unwrap();
+    assert_eq!(token.balance_of(ALICE), Ok(0));
+}
+
+#[test]
+fn vesting_needs_the_tx_context() {
+    let mut token = token();
+    vest(&mut token);
+
+    // The locked amount cannot be known without the block height
+    let action = SimpleTokenAction::Transfer {
+        recipient: BOB.to_string(),
+        amount: 1,
+    };
+    let err = Tx::new(ALICE, action).execute(&mut token).unwrap_err();
+    assert_eq!(err, SimpleTokenError::MissingTxContext.to_string());
+}
+
+#[test]
+fn fully_vested_schedule_is_dropped() {
+    let mut token = token();
+    vest(&mut token);
+    token.transfer(FAUCET, ALICE, 50).unwrap();
+    let transfer_without_context = |token: &mut SimpleToken, amount| {
+        let action = SimpleTokenAction::Transfer {
+            recipient: BOB.to_string(),
+            amount,
+        };
+        Tx::new(ALICE, action).execute(token).map(|_| ())
+    };
+
+    // What is left above the vested total is not locked, whatever the block height
+    transfer_without_context(&mut token, 50).unwrap();
+    let err = transfer_without_context(&mut token, 1).unwrap_err();
+    assert_eq!(err, SimpleTokenError::MissingTxContext.to_string());
+
+    // The first debit once fully vested drops the schedule
+    transfer(&mut token, 110, 1).unwrap();
+    assert!(token.vesting.is_empty());
+    transfer_without_context(&mut token, 1).unwrap();
+    assert_eq!(token.balance_of(ALICE), Ok(98));
+}
+
+#[test]
+fn only_one_schedule_vests_at_a_time() {
+    let mut token = token();
+    vest(&mut token);
+
+    let action = SimpleTokenAction::CreateVesting {
+        beneficiary: ALICE.to_string(),
+        amount: 10,
+        cliff: 0,
+        duration: 10,
+    };
+    let err = Tx::new(FAUCET, action.clone())
+        .at(109)
+        .execute(&mut token)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SimpleTokenError::VestingExists(ALICE.to_string()).to_string()
+    );
+
+    // Once fully vested, a new schedule replaces it
+    Tx::new(FAUCET, action).at(110).execute(&mut token).unwrap();
+    assert_eq!(token.vesting[ALICE].total, 10);
+}
+
+#[test]
+fn vested_amount_does_not_overflow() {
+    let schedule = VestingSchedule {
+        total: u128::MAX,
+        start: 0,
+        cliff: 0,
+        duration: u64::MAX,
+    };
+    assert!(schedule.vested_at(u64::MAX - 1) < u128::MAX);
+    assert_eq!(schedule.locked_at(u64::MAX), 0);
+}
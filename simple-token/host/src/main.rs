@@ -1,9 +1,20 @@
+use std::path::{Path, PathBuf};
+
 use clap::{Parser, Subcommand};
 use client_sdk::helpers::risc0::Risc0Prover;
+use client_sdk::rest_client::NodeApiHttpClient;
+use contract::hash_preimage;
+use contract::AdminAction;
+use contract::PermitPayload;
 use contract::SimpleToken;
 use contract::SimpleTokenAction;
+use contract::SimpleTokenError;
+use contract::SimpleTokenOutput;
+use contract::TokenMetadata;
+use ed25519_dalek::{Signer, SigningKey};
 use sdk::api::APIRegisterContract;
 use sdk::BlobTransaction;
+use sdk::ContractAction;
 use sdk::ProofTransaction;
 use sdk::{ContractInput, HyleContract};
 
@@ -11,6 +22,9 @@ use sdk::{ContractInput, HyleContract};
 // The ELF is used for proving and the ID is used for verification.
 use methods::{GUEST_ELF, GUEST_ID};
 
+mod merkle;
+mod swap;
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
@@ -31,16 +45,195 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Register {
-        supply: u128,
+        supply: String,
+        /// Identity allowed to mint new tokens. Defaults to the faucet account.
+        #[arg(long)]
+        minter: Option<String>,
+        /// Identity allowed to use the admin controls. Defaults to the faucet account.
+        #[arg(long)]
+        admin: Option<String>,
+        /// Maximum total supply. Unlimited if not set.
+        #[arg(long)]
+        max_supply: Option<String>,
+        #[arg(long, default_value = "Simple Token")]
+        name: String,
+        #[arg(long, default_value = "SIMPLE")]
+        symbol: String,
+        /// Number of decimals used to display amounts: with 2 decimals, `12.5` is 1250 base units
+        #[arg(long, default_value = "0")]
+        decimals: u8,
     },
     Transfer {
         from: String,
         to: String,
-        amount: u128,
+        amount: String,
     },
     Balance {
         of: String,
     },
+    /// Prints the name, symbol and decimals of the token
+    Metadata {},
+    Approve {
+        owner: String,
+        spender: String,
+        amount: String,
+    },
+    TransferFrom {
+        spender: String,
+        owner: String,
+        to: String,
+        amount: String,
+    },
+    Allowance {
+        owner: String,
+        spender: String,
+    },
+    Mint {
+        minter: String,
+        to: String,
+        amount: String,
+    },
+    /// Burns tokens of the minter
+    Burn {
+        minter: String,
+        amount: String,
+    },
+    /// Transfers from one account to all the recipients listed in a CSV file
+    BatchTransfer {
+        from: String,
+        /// CSV file with one `recipient,amount` line per transfer
+        file: PathBuf,
+    },
+    /// Transfers tokens of the admin that are released linearly over `duration` blocks, after a `cliff`
+    CreateVesting {
+        admin: String,
+        beneficiary: String,
+        amount: String,
+        /// Number of blocks before any token is released
+        #[arg(long, default_value = "0")]
+        cliff: u64,
+        /// Number of blocks after which all tokens are released
+        duration: u64,
+    },
+    /// Prints the vesting schedule of an account, and how much is still locked
+    Vesting {
+        of: String,
+    },
+    /// Takes a snapshot of the balances, sent on behalf of the `admin` identity
+    Snapshot {
+        admin: String,
+    },
+    /// Prints the balance of an account at a past snapshot
+    BalanceAt {
+        of: String,
+        snapshot_id: u64,
+    },
+    /// Locks tokens that `to` can claim with the preimage of `hashlock` until the `timeout` block height
+    LockTransfer {
+        from: String,
+        to: String,
+        amount: String,
+        /// Hex-encoded sha256 of the preimage
+        hashlock: String,
+        timeout: u64,
+    },
+    /// Claims a locked transfer, revealing the preimage of its hashlock
+    Claim {
+        caller: String,
+        lock_id: u64,
+        preimage: String,
+    },
+    /// Gives a locked transfer back to its sender, once its timeout is reached
+    Refund {
+        caller: String,
+        lock_id: u64,
+    },
+    /// Lists the pending locked transfers
+    Locks {},
+    /// Prints the hashlock matching a preimage
+    Hashlock {
+        preimage: String,
+    },
+    /// Sets the key verifying the permits of `owner`, from its hex-encoded ed25519 secret key
+    SetPermitKey {
+        owner: String,
+        secret_key: String,
+    },
+    /// Signs a permit offline, and prints the hex-encoded payload to send with `permit`
+    SignPermit {
+        owner: String,
+        spender: String,
+        /// Amount in base units, as the token decimals are not fetched offline
+        amount: u128,
+        nonce: u64,
+        /// Block height until which the permit is valid
+        deadline: u64,
+        /// Hex-encoded ed25519 secret key of `owner`
+        secret_key: String,
+    },
+    /// Sends a permit signed with `sign-permit`, on behalf of any identity
+    Permit {
+        caller: String,
+        payload: String,
+    },
+    /// Prints the nonce the next permit of `owner` must be signed with
+    PermitNonce {
+        owner: String,
+    },
+    /// Claims the drip amount from the faucet, on behalf of `identity`
+    Drip {
+        identity: String,
+    },
+    /// Swaps tokens of this contract against tokens of another one, with locked transfers on both sides
+    Swap(swap::SwapArgs),
+    /// Admin controls, sent on behalf of the `admin` identity
+    Admin {
+        admin: String,
+
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
+    /// Commands for a token registered in Merkle mode, where only the root of the balances is on-chain
+    Merkle {
+        /// File holding the full balances, used to build the proofs
+        #[arg(long, default_value = "merkle_balances.json")]
+        store: PathBuf,
+        /// Number of decimals used to read and display amounts, as the Merkle state holds no metadata
+        #[arg(long, default_value = "0")]
+        decimals: u8,
+
+        #[command(subcommand)]
+        command: merkle::MerkleCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum AdminCommands {
+    /// Blocks all transfers
+    Pause,
+    Unpause,
+    /// Prevents an account from sending or receiving tokens
+    Freeze {
+        account: String,
+    },
+    Unfreeze {
+        account: String,
+    },
+    /// Only lets accounts in the allow-list send or receive tokens
+    EnableAllowList,
+    DisableAllowList,
+    Allow {
+        account: String,
+    },
+    Disallow {
+        account: String,
+    },
+    /// Lets anyone claim `amount` from the faucet, at most once every `cooldown` blocks
+    SetDrip {
+        amount: String,
+        cooldown: u64,
+    },
+    DisableDrip,
 }
 
 #[tokio::main]
@@ -52,18 +245,39 @@ async fn main() {
 
     let cli = Cli::parse();
 
-    let client = client_sdk::rest_client::NodeApiHttpClient::new(cli.host).unwrap();
+    let client = NodeApiHttpClient::new(cli.host).unwrap();
 
     let contract_name = &cli.contract_name;
 
     let prover = Risc0Prover::new(GUEST_ELF);
 
     match cli.command {
-        Commands::Register { supply } => {
+        Commands::Register {
+            supply,
+            minter,
+            admin,
+            max_supply,
+            name,
+            symbol,
+            decimals,
+        } => {
             // Build initial state of contract
+            let metadata = TokenMetadata {
+                name,
+                symbol,
+                decimals,
+            };
+            let supply = parse_amount(&metadata, &supply);
+            let max_supply = max_supply.map(|max_supply| parse_amount(&metadata, &max_supply));
+            let faucet = format!("faucet.{}", contract_name);
+            let minter = minter.unwrap_or_else(|| faucet.clone());
+            let admin = admin.unwrap_or_else(|| faucet.clone());
             let initial_state =
-                SimpleToken::new(supply, format!("faucet.{}", contract_name).into());
+                SimpleToken::new(supply, faucet, minter, max_supply, metadata, admin);
             println!("Initial state: {:?}", initial_state);
+            if let Err(err) = initial_state.check_supply() {
+                exit_with(err);
+            }
 
             // Send the transaction to register the contract
             let res = client
@@ -79,74 +293,585 @@ async fn main() {
         }
         Commands::Balance { of } => {
             // Fetch the state from the node
-            let state: SimpleToken = client
-                .get_contract(&contract_name.clone().into())
-                .await
-                .unwrap()
-                .state
-                .into();
+            let state = fetch_state(&client, contract_name).await;
 
             println!("Balances {:?}", &state);
 
-            let balance = state.balance_of(&of).unwrap();
-            println!("Balance of {}: {}", of, balance);
+            let balance = state.balance_of(&of).unwrap_or_else(|err| exit_with(err));
+            println!(
+                "Balance of {}: {} {}",
+                of,
+                state.metadata.format_amount(balance),
+                state.metadata.symbol
+            );
+        }
+        Commands::Metadata {} => {
+            let state = fetch_state(&client, contract_name).await;
+            let metadata = state.metadata();
+            println!("Name: {}", metadata.name);
+            println!("Symbol: {}", metadata.symbol);
+            println!("Decimals: {}", metadata.decimals);
+            println!(
+                "Total supply: {} {}",
+                metadata.format_amount(state.total_supply),
+                metadata.symbol
+            );
         }
         Commands::Transfer { from, to, amount } => {
-            // Fetch the initial state from the node
-            let mut initial_state: SimpleToken = client
-                .get_contract(&contract_name.clone().into())
-                .await
-                .unwrap()
-                .state
-                .into();
-            // ----
-            // Build the blob transaction
-            // ----
-
+            let state = fetch_state(&client, contract_name).await;
             let action = SimpleTokenAction::Transfer {
-                recipient: to.clone(),
+                recipient: to,
+                amount: parse_amount(&state.metadata, &amount),
+            };
+            send_action(&client, &prover, contract_name, state, from, action, vec![]).await;
+        }
+        Commands::Approve {
+            owner,
+            spender,
+            amount,
+        } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::Approve {
+                spender,
+                amount: parse_amount(&state.metadata, &amount),
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                owner,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::TransferFrom {
+            spender,
+            owner,
+            to,
+            amount,
+        } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::TransferFrom {
+                owner,
+                recipient: to,
+                amount: parse_amount(&state.metadata, &amount),
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                spender,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::Mint { minter, to, amount } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::Mint {
+                recipient: to,
+                amount: parse_amount(&state.metadata, &amount),
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                minter,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::Burn { minter, amount } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::Burn {
+                amount: parse_amount(&state.metadata, &amount),
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                minter,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::BatchTransfer { from, file } => {
+            let state = fetch_state(&client, contract_name).await;
+            let transfers = read_transfers(&state.metadata, &file);
+            println!("Sending {} transfers from {}", transfers.len(), from);
+            let action = SimpleTokenAction::BatchTransfer { transfers };
+            send_action(&client, &prover, contract_name, state, from, action, vec![]).await;
+        }
+        Commands::CreateVesting {
+            admin,
+            beneficiary,
+            amount,
+            cliff,
+            duration,
+        } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::CreateVesting {
+                beneficiary,
+                amount: parse_amount(&state.metadata, &amount),
+                cliff,
+                duration,
+            };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                admin,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::Vesting { of } => {
+            let mut state = fetch_state(&client, contract_name).await;
+            let Some(schedule) = state.vesting.get(&of).cloned() else {
+                println!("No vesting schedule for {}", of);
+                return;
+            };
+            state.block_height = Some(client.get_block_height().await.unwrap().0);
+
+            let locked = state
+                .locked_balance(&of)
+                .unwrap_or_else(|err| exit_with(err));
+            let format = |amount| state.metadata.format_amount(amount);
+            println!(
+                "Vesting of {}: {} {} from block {}, cliff {} blocks, duration {} blocks",
+                of,
+                format(schedule.total),
+                state.metadata.symbol,
+                schedule.start,
+                schedule.cliff,
+                schedule.duration
+            );
+            println!(
+                "Locked at block {}: {} {}",
+                state.block_height.unwrap(),
+                format(locked),
+                state.metadata.symbol
+            );
+        }
+        Commands::Snapshot { admin } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::Snapshot {};
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                admin,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::BalanceAt { of, snapshot_id } => {
+            let state = fetch_state(&client, contract_name).await;
+
+            let balance = state
+                .balance_at(&of, snapshot_id)
+                .unwrap_or_else(|err| exit_with(err));
+            println!(
+                "Balance of {} at snapshot {}: {} {}",
+                of,
+                snapshot_id,
+                state.metadata.format_amount(balance),
+                state.metadata.symbol
+            );
+        }
+        Commands::LockTransfer {
+            from,
+            to,
+            amount,
+            hashlock,
+            timeout,
+        } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::LockTransfer {
+                recipient: to,
+                amount: parse_amount(&state.metadata, &amount),
+                hashlock: parse_hashlock(&hashlock),
+                timeout,
+            };
+            send_action(&client, &prover, contract_name, state, from, action, vec![]).await;
+        }
+        Commands::Claim {
+            caller,
+            lock_id,
+            preimage,
+        } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::Claim { lock_id };
+            let private_input = preimage.into_bytes();
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                caller,
+                action,
+                private_input,
+            )
+            .await;
+        }
+        Commands::Refund { caller, lock_id } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::Refund { lock_id };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                caller,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::Locks {} => {
+            let state = fetch_state(&client, contract_name).await;
+            for (lock_id, lock) in &state.locks {
+                println!(
+                    "Lock {}: {} {} from {} to {}, hashlock {}, until block {}",
+                    lock_id,
+                    state.metadata.format_amount(lock.amount),
+                    state.metadata.symbol,
+                    lock.sender,
+                    lock.recipient,
+                    hex::encode(lock.hashlock),
+                    lock.timeout
+                );
+            }
+        }
+        Commands::Hashlock { preimage } => {
+            println!("{}", hex::encode(hash_preimage(preimage.as_bytes())));
+        }
+        Commands::SetPermitKey { owner, secret_key } => {
+            let public_key = parse_signing_key(&secret_key).verifying_key().to_bytes();
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::SetPermitKey { public_key };
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                owner,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::SignPermit {
+            owner,
+            spender,
+            amount,
+            nonce,
+            deadline,
+            secret_key,
+        } => {
+            let payload = PermitPayload {
+                contract: contract_name.clone(),
+                owner,
+                spender,
                 amount,
+                nonce,
+                deadline,
             };
-            let blobs = vec![sdk::Blob {
-                contract_name: contract_name.clone().into(),
-                data: sdk::BlobData(borsh::to_vec(&action).expect("failed to encode BlobData")),
-            }];
-            let blob_tx = BlobTransaction::new(from.clone(), blobs.clone());
-
-            // Send the blob transaction
-            let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
-            println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
-
-            // ----
-            // Prove the state transition
-            // ----
-
-            // Build the contract input
-            let inputs = ContractInput {
-                state: initial_state.as_bytes().unwrap(),
-                identity: from.clone().into(),
-                tx_hash: blob_tx_hash,
-                private_input: vec![],
-                tx_ctx: None,
-                blobs: blobs.clone(),
-                index: sdk::BlobIndex(0),
+            let signature = parse_signing_key(&secret_key).sign(&payload.signing_bytes());
+            let action = SimpleTokenAction::Permit {
+                owner: payload.owner,
+                spender: payload.spender,
+                amount: payload.amount,
+                nonce: payload.nonce,
+                deadline: payload.deadline,
+                signature: signature.to_bytes().to_vec(),
+            };
+            println!("{}", hex::encode(borsh::to_vec(&action).unwrap()));
+        }
+        Commands::Permit { caller, payload } => {
+            let action: SimpleTokenAction = hex::decode(&payload)
+                .ok()
+                .and_then(|bytes| borsh::from_slice(&bytes).ok())
+                .filter(|action| matches!(action, SimpleTokenAction::Permit { .. }))
+                .unwrap_or_else(|| {
+                    eprintln!("❌ Payload is not a permit produced by `sign-permit`");
+                    std::process::exit(1);
+                });
+            let state = fetch_state(&client, contract_name).await;
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                caller,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::PermitNonce { owner } => {
+            let state = fetch_state(&client, contract_name).await;
+            println!("Permit nonce of {}: {}", owner, state.permit_nonce(&owner));
+        }
+        Commands::Drip { identity } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = SimpleTokenAction::Drip {};
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                identity,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::Swap(args) => {
+            swap::run(&client, &prover, contract_name, args).await;
+        }
+        Commands::Admin { admin, command } => {
+            let state = fetch_state(&client, contract_name).await;
+            let action = match command {
+                AdminCommands::Pause => AdminAction::Pause,
+                AdminCommands::Unpause => AdminAction::Unpause,
+                AdminCommands::Freeze { account } => AdminAction::Freeze { account },
+                AdminCommands::Unfreeze { account } => AdminAction::Unfreeze { account },
+                AdminCommands::EnableAllowList => AdminAction::EnableAllowList,
+                AdminCommands::DisableAllowList => AdminAction::DisableAllowList,
+                AdminCommands::Allow { account } => AdminAction::Allow { account },
+                AdminCommands::Disallow { account } => AdminAction::Disallow { account },
+                AdminCommands::SetDrip { amount, cooldown } => AdminAction::SetDrip {
+                    amount: parse_amount(&state.metadata, &amount),
+                    cooldown,
+                },
+                AdminCommands::DisableDrip => AdminAction::DisableDrip,
             };
+            let action = SimpleTokenAction::Admin(action);
+            send_action(
+                &client,
+                &prover,
+                contract_name,
+                state,
+                admin,
+                action,
+                vec![],
+            )
+            .await;
+        }
+        Commands::Merkle {
+            store,
+            decimals,
+            command,
+        } => {
+            merkle::run(&client, contract_name, &store, decimals, command).await;
+        }
+        Commands::Allowance { owner, spender } => {
+            // Fetch the state from the node
+            let state = fetch_state(&client, contract_name).await;
 
-            let res = initial_state.execute(&inputs).unwrap();
-            println!("🚀 Executed: {}", res.0);
+            let allowance = state.allowance(&owner, &spender);
+            println!(
+                "Allowance of {} over {}: {} {}",
+                spender,
+                owner,
+                state.metadata.format_amount(allowance),
+                state.metadata.symbol
+            );
+        }
+    }
+}
 
-            // Generate the zk proof
-            //
-            let proof = prover.prove(inputs).await.unwrap();
+/// Fetches the current state of the contract from the node.
+async fn fetch_state(client: &NodeApiHttpClient, contract_name: &str) -> SimpleToken {
+    client
+        .get_contract(&contract_name.into())
+        .await
+        .unwrap()
+        .state
+        .into()
+}
 
-            let proof_tx = ProofTransaction {
-                proof,
-                contract_name: contract_name.clone().into(),
-            };
+/// Parses a decimal amount such as `12.5` into base units, according to the token decimals.
+fn parse_amount(metadata: &TokenMetadata, amount: &str) -> u128 {
+    metadata.parse_amount(amount).unwrap_or_else(|err| {
+        eprintln!("❌ {}", err);
+        std::process::exit(1);
+    })
+}
+
+/// Sends a blob transaction for `action` on behalf of `identity`, then proves and sends its proof.
+/// `initial_state` is the current state of the contract, as fetched from the node.
+/// `private_input` is only given to the prover, it is not sent on-chain.
+async fn send_action(
+    client: &NodeApiHttpClient,
+    prover: &Risc0Prover<'_>,
+    contract_name: &str,
+    mut initial_state: SimpleToken,
+    identity: String,
+    action: SimpleTokenAction,
+    private_input: Vec<u8>,
+) {
+    // Run the action locally first, so that failures are reported before sending anything.
+    // The transaction is not in a block yet, so vesting is checked against the current height.
+    let mut dry_run_state = initial_state.clone();
+    dry_run_state.block_height = Some(client.get_block_height().await.unwrap().0);
+    dry_run_state.contract_name = contract_name.to_string();
+    if let Err(err) = dry_run_state.execute_action(&identity, action.clone(), &private_input) {
+        exit_with(err);
+    }
 
-            // Send the proof transaction
-            let proof_tx_hash = client.send_tx_proof(&proof_tx).await.unwrap();
-            println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+    // ----
+    // Build the blob transaction
+    // ----
+
+    let blobs = vec![action.as_blob(contract_name.into(), None, None)];
+    let blob_tx = BlobTransaction::new(identity.clone(), blobs.clone());
+
+    // Send the blob transaction
+    let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
+    println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
+
+    // ----
+    // Prove the state transition
+    // ----
+
+    // The context of the transaction is known once it is included in a block
+    let tx_ctx = fetch_tx_ctx(client, &blob_tx_hash).await;
+
+    // Build the contract input
+    let inputs = ContractInput {
+        state: initial_state.as_bytes().unwrap(),
+        identity: identity.into(),
+        tx_hash: blob_tx_hash,
+        private_input,
+        tx_ctx: Some(tx_ctx),
+        blobs: blobs.clone(),
+        index: sdk::BlobIndex(0),
+    };
+
+    let res = initial_state.execute(&inputs).unwrap();
+    let output = SimpleTokenOutput::decode(&res.0).expect("failed to decode program output");
+    for event in output.events() {
+        println!("🚀 Executed: {}", event);
+    }
+
+    // Generate the zk proof
+    //
+    let proof = prover.prove(inputs).await.unwrap();
+
+    let proof_tx = ProofTransaction {
+        proof,
+        contract_name: contract_name.into(),
+    };
+
+    // Send the proof transaction
+    let proof_tx_hash = client.send_tx_proof(&proof_tx).await.unwrap();
+    println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+}
+
+/// Parses a hex-encoded hashlock, exits if it is not 32 bytes long.
+fn parse_hashlock(hashlock: &str) -> [u8; 32] {
+    hex::decode(hashlock)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .unwrap_or_else(|| {
+            eprintln!("❌ Hashlock must be 32 hex-encoded bytes");
+            std::process::exit(1);
+        })
+}
+
+/// Parses a hex-encoded ed25519 secret key, exits if it is not 32 bytes long.
+fn parse_signing_key(secret_key: &str) -> SigningKey {
+    hex::decode(secret_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(|bytes| SigningKey::from_bytes(&bytes))
+        .unwrap_or_else(|| {
+            eprintln!("❌ Secret key must be 32 hex-encoded bytes");
+            std::process::exit(1);
+        })
+}
+
+/// Waits for the blob transaction to be included in a block, and returns its context.
+async fn fetch_tx_ctx(client: &NodeApiHttpClient, tx_hash: &sdk::TxHash) -> sdk::TxContext {
+    for _ in 0..30 {
+        if let Ok(tx) = client.get_unsettled_tx(tx_hash).await {
+            return tx.tx_context;
         }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     }
+    panic!("blob tx {} was not included in a block", tx_hash);
+}
+
+/// Reads `recipient,amount` lines from a CSV file, skipping empty lines and `#` comments.
+/// Exits at the first line that cannot be parsed.
+fn read_transfers(metadata: &TokenMetadata, file: &Path) -> Vec<(String, u128)> {
+    let exit = |err: String| -> ! {
+        eprintln!("❌ {}", err);
+        std::process::exit(1);
+    };
+    let content = std::fs::read_to_string(file)
+        .unwrap_or_else(|err| exit(format!("Cannot read {}: {err}", file.display())));
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            let (recipient, amount) = line.split_once(',').unwrap_or_else(|| {
+                exit(format!("Line {line_number}: expected `recipient,amount`"))
+            });
+            let amount = metadata
+                .parse_amount(amount.trim())
+                .unwrap_or_else(|err| exit(format!("Line {line_number}: {err}")));
+            (recipient.trim().to_string(), amount)
+        })
+        .collect()
+}
+
+/// Prints `err` and exits with a code specific to its kind, so that scripts can match on it.
+fn exit_with(err: SimpleTokenError) -> ! {
+    let code = match err {
+        SimpleTokenError::AccountNotFound(_) => 2,
+        SimpleTokenError::InsufficientBalance { .. } => 3,
+        SimpleTokenError::InsufficientAllowance { .. } => 4,
+        SimpleTokenError::Overflow => 5,
+        SimpleTokenError::Unauthorized(_) => 6,
+        SimpleTokenError::MaxSupplyExceeded { .. } => 7,
+        SimpleTokenError::SupplyMismatch { .. } => 8,
+        SimpleTokenError::InvalidProof(_) => 9,
+        SimpleTokenError::Paused => 10,
+        SimpleTokenError::AccountFrozen(_) => 11,
+        SimpleTokenError::NotAllowed(_) => 12,
+        SimpleTokenError::VestingLocked { .. } => 13,
+        SimpleTokenError::VestingExists(_) => 14,
+        SimpleTokenError::MissingTxContext => 15,
+        SimpleTokenError::SnapshotNotFound(_) => 16,
+        SimpleTokenError::LockNotFound(_) => 17,
+        SimpleTokenError::InvalidPreimage(_) => 18,
+        SimpleTokenError::LockExpired { .. } => 19,
+        SimpleTokenError::LockNotExpired { .. } => 20,
+        SimpleTokenError::PermitKeyNotSet(_) => 21,
+        SimpleTokenError::InvalidNonce { .. } => 22,
+        SimpleTokenError::InvalidSignature => 23,
+        SimpleTokenError::PermitExpired(_) => 24,
+        SimpleTokenError::DripDisabled => 25,
+        SimpleTokenError::DripCooldown { .. } => 26,
+        SimpleTokenError::InvalidTimeout { .. } => 27,
+        SimpleTokenError::InvalidPublicKey => 28,
+    };
+    eprintln!("❌ {}", err);
+    std::process::exit(code);
 }
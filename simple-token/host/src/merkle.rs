@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use clap::Subcommand;
+use client_sdk::helpers::risc0::Risc0Prover;
+use client_sdk::rest_client::NodeApiHttpClient;
+use contract::merkle::{BalanceTree, MerkleToken};
+use contract::SimpleTokenAction;
+use contract::SimpleTokenError;
+use contract::TokenMetadata;
+use sdk::api::APIRegisterContract;
+use sdk::BlobTransaction;
+use sdk::ContractAction;
+use sdk::ProofTransaction;
+use sdk::{ContractInput, HyleContract};
+
+use methods::{MERKLE_ELF, MERKLE_ID};
+
+use crate::{exit_with, fetch_tx_ctx, parse_amount};
+
+#[derive(Subcommand)]
+pub enum MerkleCommands {
+    Register {
+        supply: String,
+    },
+    Transfer {
+        from: String,
+        to: String,
+        amount: String,
+    },
+    Balance {
+        of: String,
+    },
+}
+
+/// Runs a command against a token registered in Merkle mode.
+/// The full balances are stored in the `store` file, only their root is on-chain.
+/// The Merkle state holds no metadata, so amounts are read with the `decimals` given on the command line.
+pub async fn run(
+    client: &NodeApiHttpClient,
+    contract_name: &str,
+    store: &Path,
+    decimals: u8,
+    command: MerkleCommands,
+) {
+    let prover = Risc0Prover::new(MERKLE_ELF);
+    let metadata = TokenMetadata {
+        name: contract_name.to_string(),
+        symbol: contract_name.to_string(),
+        decimals,
+    };
+
+    match command {
+        MerkleCommands::Register { supply } => {
+            // Build initial balances, the supply is assigned to the faucet
+            let supply = parse_amount(&metadata, &supply);
+            let mut tree = BalanceTree::default();
+            tree.set_balance(&format!("faucet.{}", contract_name), supply);
+            let initial_state = MerkleToken::new(tree.root());
+            println!("Initial state: {:?}", initial_state);
+
+            // Send the transaction to register the contract
+            let res = client
+                .register_contract(&APIRegisterContract {
+                    verifier: "risc0-1".into(),
+                    program_id: sdk::ProgramId(sdk::to_u8_array(&MERKLE_ID).to_vec()),
+                    state_commitment: initial_state.commit(),
+                    contract_name: contract_name.into(),
+                })
+                .await
+                .unwrap();
+            println!("✅ Register contract tx sent. Tx hash: {}", res);
+
+            save_tree(store, &tree);
+        }
+        MerkleCommands::Balance { of } => {
+            let tree = load_tree(client, contract_name, store).await.0;
+            println!(
+                "Balance of {}: {}",
+                of,
+                metadata.format_amount(tree.balance_of(&of))
+            );
+        }
+        MerkleCommands::Transfer { from, to, amount } => {
+            let amount = parse_amount(&metadata, &amount);
+            let (mut tree, initial_state) = load_tree(client, contract_name, store).await;
+
+            // ----
+            // Build the proofs of the touched balances, each against the tree updated by the previous one
+            // ----
+
+            let from_balance = tree.balance_of(&from);
+            let from_proof = tree.proof(&from);
+            tree.set_balance(&from, from_balance.saturating_sub(amount));
+            let to_balance = tree.balance_of(&to);
+            let to_proof = tree.proof(&to);
+            tree.set_balance(&to, to_balance.saturating_add(amount));
+            let proofs = vec![from_proof, to_proof];
+
+            // Run the transfer locally first, so that failures are reported before sending anything
+            if let Err(err) = initial_state
+                .clone()
+                .transfer(&from, &to, amount, proofs.clone())
+            {
+                exit_with(err);
+            }
+
+            // ----
+            // Build the blob transaction
+            // ----
+
+            let action = SimpleTokenAction::Transfer {
+                recipient: to,
+                amount,
+            };
+            let blobs = vec![action.as_blob(contract_name.into(), None, None)];
+            let blob_tx = BlobTransaction::new(from.clone(), blobs.clone());
+
+            // Send the blob transaction
+            let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
+            println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
+
+            // ----
+            // Prove the state transition
+            // ----
+
+            // The context of the transaction is known once it is included in a block
+            let tx_ctx = fetch_tx_ctx(client, &blob_tx_hash).await;
+
+            // Build the contract input, the touched balances are sent as private input
+            let inputs = ContractInput {
+                state: borsh::to_vec(&initial_state).unwrap(),
+                identity: from.into(),
+                tx_hash: blob_tx_hash,
+                private_input: borsh::to_vec(&proofs).unwrap(),
+                tx_ctx: Some(tx_ctx),
+                blobs: blobs.clone(),
+                index: sdk::BlobIndex(0),
+            };
+
+            // Generate the zk proof
+            let proof = prover.prove(inputs).await.unwrap();
+
+            let proof_tx = ProofTransaction {
+                proof,
+                contract_name: contract_name.into(),
+            };
+
+            // Send the proof transaction
+            let proof_tx_hash = client.send_tx_proof(&proof_tx).await.unwrap();
+            println!("✅ Proof tx sent. Tx hash: {}", proof_tx_hash);
+
+            // The store now holds the balances the settled transaction will commit to
+            save_tree(store, &tree);
+        }
+    }
+}
+
+/// Loads the off-chain balances, and checks that they match the on-chain root.
+async fn load_tree(
+    client: &NodeApiHttpClient,
+    contract_name: &str,
+    store: &Path,
+) -> (BalanceTree, MerkleToken) {
+    let tree: BalanceTree = std::fs::read(store)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_else(|| {
+            eprintln!("❌ No valid balance store found at {}", store.display());
+            std::process::exit(1);
+        });
+
+    // Fetch the root from the node
+    let state: MerkleToken = client
+        .get_contract(&contract_name.into())
+        .await
+        .unwrap()
+        .state
+        .into();
+
+    if tree.root() != state.root {
+        exit_with(SimpleTokenError::InvalidProof(format!(
+            "Balance store {} does not match the on-chain root, is a transaction still pending?",
+            store.display()
+        )));
+    }
+
+    (tree, state)
+}
+
+fn save_tree(store: &Path, tree: &BalanceTree) {
+    let content = serde_json::to_vec_pretty(tree).expect("failed to encode balance store");
+    std::fs::write(store, content).expect("failed to write balance store");
+}
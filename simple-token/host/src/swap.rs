@@ -0,0 +1,135 @@
+use clap::Args;
+use client_sdk::helpers::risc0::Risc0Prover;
+use client_sdk::rest_client::NodeApiHttpClient;
+use contract::{hash_preimage, SimpleToken, SimpleTokenAction};
+
+use crate::{fetch_state, parse_amount, send_action};
+
+#[derive(Args)]
+pub struct SwapArgs {
+    /// Identity giving `amount` tokens of this contract, and knowing the secret
+    alice: String,
+    amount: String,
+    /// Identity giving `other_amount` tokens of the other contract
+    bob: String,
+    other_contract: String,
+    other_amount: String,
+    /// Preimage of the hashlock locking both transfers
+    secret: String,
+    /// Number of blocks `bob` has to claim before the lock of `alice` times out.
+    /// The lock of `bob` times out after that many blocks, the one of `alice` after twice as many.
+    #[arg(long, default_value = "100")]
+    timeout: u64,
+}
+
+/// Runs both sides of an atomic swap between `contract_name` and `args.other_contract`:
+///
+/// 1. `alice` locks her tokens for `bob`, with the hash of the secret.
+/// 2. `bob` locks his tokens for `alice` on the other contract, with the same hash and a shorter timeout.
+/// 3. `alice` claims the tokens of `bob`, which reveals the secret in the program output.
+/// 4. `bob` claims the tokens of `alice` with the secret.
+///
+/// If a side stops before claiming, both locks can be refunded once their timeouts are reached.
+pub async fn run(
+    client: &NodeApiHttpClient,
+    prover: &Risc0Prover<'_>,
+    contract_name: &str,
+    args: SwapArgs,
+) {
+    let secret = args.secret.into_bytes();
+    let hashlock = hash_preimage(&secret);
+    let block_height = client.get_block_height().await.unwrap().0;
+
+    println!(
+        "🔒 {} locks {} on {}",
+        args.alice, args.amount, contract_name
+    );
+    let state = fetch_state(client, contract_name).await;
+    let alice_lock = state.next_lock_id;
+    let action = SimpleTokenAction::LockTransfer {
+        recipient: args.bob.clone(),
+        amount: parse_amount(&state.metadata, &args.amount),
+        hashlock,
+        timeout: block_height + 2 * args.timeout,
+    };
+    send_action(
+        client,
+        prover,
+        contract_name,
+        state,
+        args.alice.clone(),
+        action,
+        vec![],
+    )
+    .await;
+    wait_for_lock(client, contract_name, alice_lock).await;
+
+    println!(
+        "🔒 {} locks {} on {}",
+        args.bob, args.other_amount, args.other_contract
+    );
+    let state = fetch_state(client, &args.other_contract).await;
+    let bob_lock = state.next_lock_id;
+    let action = SimpleTokenAction::LockTransfer {
+        recipient: args.alice.clone(),
+        amount: parse_amount(&state.metadata, &args.other_amount),
+        hashlock,
+        timeout: block_height + args.timeout,
+    };
+    send_action(
+        client,
+        prover,
+        &args.other_contract,
+        state,
+        args.bob.clone(),
+        action,
+        vec![],
+    )
+    .await;
+    let state = wait_for_lock(client, &args.other_contract, bob_lock).await;
+
+    println!("🔓 {} claims on {}", args.alice, args.other_contract);
+    let action = SimpleTokenAction::Claim { lock_id: bob_lock };
+    send_action(
+        client,
+        prover,
+        &args.other_contract,
+        state,
+        args.alice,
+        action,
+        secret.clone(),
+    )
+    .await;
+
+    println!("🔓 {} claims on {}", args.bob, contract_name);
+    let state = fetch_state(client, contract_name).await;
+    let action = SimpleTokenAction::Claim {
+        lock_id: alice_lock,
+    };
+    send_action(
+        client,
+        prover,
+        contract_name,
+        state,
+        args.bob,
+        action,
+        secret,
+    )
+    .await;
+}
+
+/// Waits for the lock to be settled, and returns the state of the contract holding it.
+async fn wait_for_lock(
+    client: &NodeApiHttpClient,
+    contract_name: &str,
+    lock_id: u64,
+) -> SimpleToken {
+    for _ in 0..60 {
+        let state = fetch_state(client, contract_name).await;
+        if state.locks.contains_key(&lock_id) {
+            return state;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    panic!("lock {} on {} was not settled", lock_id, contract_name);
+}
@@ -8,25 +8,43 @@ impl sdk::HyleContract for TicketAppState {
     /// Entry point of the contract's logic
     fn execute(&mut self, contract_input: &sdk::ContractInput) -> RunResult {
         let (ticket_app_action, ctx) =
-            sdk::utils::parse_raw_contract_input::<TicketAppAction>(contract_input)?;
+            sdk::utils::parse_contract_input::<TicketAppAction>(contract_input)?;
 
-        let transfer_action = sdk::utils::parse_blob::<SimpleTokenAction>(
+        // The ticket app never acts through other blobs: a callee would let a blob of the transaction
+        // spend the tokens owned by the ticket app, and a caller would let another contract buy in its name
+        let ticket_app_blob = sdk::utils::parse_structured_blob::<TicketAppAction>(
+            contract_input.blobs.as_slice(),
+            &contract_input.index,
+        )
+        .ok_or("failed to parse action")?;
+        if ticket_app_blob.data.caller.is_some() {
+            return Err("Ticket app blob must not have a caller".to_string());
+        }
+        if ticket_app_blob.data.callees.is_some() || !ctx.callees_blobs.borrow().is_empty() {
+            return Err("Ticket app blob must not have callees".to_string());
+        }
+
+        let transfer_blob = sdk::utils::parse_structured_blob::<SimpleTokenAction>(
             contract_input.blobs.as_slice(),
             &BlobIndex(1),
         )
         .ok_or("failed to parse action")?;
 
-        let transfer_action_contract_name =
-            contract_input.blobs.get(1).unwrap().contract_name.clone();
+        // The ticket is paid by the buyer: a transfer with a caller would debit the calling contract instead
+        if transfer_blob.data.caller.is_some() {
+            return Err("Ticket must be paid by the buyer, without caller".to_string());
+        }
 
-        let res = match ticket_app_action {
-            TicketAppAction::BuyTicket {} => {
-                self.buy_ticket(&ctx, transfer_action, transfer_action_contract_name)?
-            }
+        let event = match ticket_app_action {
+            TicketAppAction::BuyTicket {} => self.buy_ticket(
+                &ctx,
+                transfer_blob.data.parameters,
+                transfer_blob.contract_name,
+            )?,
             TicketAppAction::HasTicket {} => self.has_ticket(&ctx)?,
         };
 
-        Ok((res, ctx, vec![]))
+        Ok((TicketAppOutput::V1(event).encode(), ctx, vec![]))
     }
 
     /// In this example, we serialize the full state on-chain.
@@ -41,6 +59,76 @@ pub enum TicketAppAction {
     HasTicket {},
 }
 
+impl sdk::ContractAction for TicketAppAction {
+    fn as_blob(
+        &self,
+        contract_name: ContractName,
+        caller: Option<BlobIndex>,
+        callees: Option<Vec<BlobIndex>>,
+    ) -> sdk::Blob {
+        sdk::Blob {
+            contract_name,
+            data: sdk::BlobData::from(sdk::StructuredBlobData {
+                caller,
+                callees,
+                parameters: self.clone(),
+            }),
+        }
+    }
+}
+
+/// Enum representing the events emitted by the ticket app.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum TicketAppEvent {
+    TicketBought {
+        buyer: Identity,
+        token: ContractName,
+        amount: u128,
+    },
+    TicketPresent {
+        owner: Identity,
+    },
+}
+
+/// The program output of the contract: the event emitted by the executed action.
+/// It is versioned so that indexers can keep decoding the outputs of older versions of the contract.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum TicketAppOutput {
+    V1(TicketAppEvent),
+}
+
+impl std::fmt::Display for TicketAppEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TicketAppEvent::TicketBought {
+                buyer,
+                token,
+                amount,
+            } => write!(f, "Ticket bought by {} for {amount} {}", buyer.0, token.0),
+            TicketAppEvent::TicketPresent { owner } => write!(f, "Ticket present for {}", owner.0),
+        }
+    }
+}
+
+impl TicketAppOutput {
+    /// Encodes the output as the string returned by `execute`: the sdk expects a string,
+    /// so the borsh encoding is hex-encoded.
+    pub fn encode(&self) -> String {
+        hex::encode(borsh::to_vec(self).expect("Failed to encode TicketAppOutput"))
+    }
+
+    pub fn decode(output: &str) -> Result<Self, String> {
+        let bytes = hex::decode(output).map_err(|e| e.to_string())?;
+        borsh::from_slice(&bytes).map_err(|e| e.to_string())
+    }
+
+    pub fn event(&self) -> &TicketAppEvent {
+        match self {
+            TicketAppOutput::V1(event) => event,
+        }
+    }
+}
+
 /// The state of the contract, that is totally serialized on-chain
 #[derive(Debug, Serialize, Deserialize, Clone, BorshSerialize, BorshDeserialize, Default)]
 pub struct TicketAppState {
@@ -61,14 +149,14 @@ impl TicketAppState {
         ctx: &ExecutionContext,
         erc20_action: SimpleTokenAction,
         erc20_name: ContractName,
-    ) -> Result<String, String> {
+    ) -> Result<TicketAppEvent, String> {
         // Check that a blob exists matching the given action, pop it from the callee blobs.
 
         if self.tickets.contains(&ctx.caller) {
             return Err(format!("Ticket already present for {:?}", &ctx.caller));
         }
 
-        match erc20_action {
+        let amount = match erc20_action {
             SimpleTokenAction::Transfer { recipient, amount } => {
                 if recipient != ctx.contract_name.0 {
                     return Err(format!(
@@ -90,21 +178,28 @@ impl TicketAppState {
                         self.ticket_price.0, &recipient
                     ));
                 }
-            }
-        }
 
-        let program_outputs = format!("Ticket created for {:?}", ctx.caller);
+                amount
+            }
+            _ => return Err("Ticket must be paid with a Transfer action".to_string()),
+        };
 
         self.tickets.push(ctx.caller.clone());
 
-        Ok(program_outputs)
+        Ok(TicketAppEvent::TicketBought {
+            buyer: ctx.caller.clone(),
+            token: erc20_name,
+            amount,
+        })
     }
 
-    pub fn has_ticket(&self, ctx: &ExecutionContext) -> Result<String, String> {
+    pub fn has_ticket(&self, ctx: &ExecutionContext) -> Result<TicketAppEvent, String> {
         // Check that a blob exists matching the given action, pop it from the callee blobs.
 
         if self.tickets.contains(&ctx.caller) {
-            Ok(format!("Ticket present for {:?}", &ctx.caller))
+            Ok(TicketAppEvent::TicketPresent {
+                owner: ctx.caller.clone(),
+            })
         } else {
             Err(format!("No Ticket for {:?}", &ctx.caller))
         }
@@ -0,0 +1,112 @@
+use contract_ticket_app::{TicketAppAction, TicketAppState};
+use sdk::{
+    Blob, BlobData, BlobIndex, ContractAction, ContractInput, HyleContract, Identity, TxHash,
+};
+use simple_token::{SimpleToken, SimpleTokenAction, TokenMetadata};
+
+const TICKET_APP: &str = "simple_ticket_app";
+const TOKEN: &str = "simple_token";
+const ATTACKER: &str = "mallory.id";
+
+fn ticket_app() -> TicketAppState {
+    TicketAppState::new(vec![ATTACKER.into()], (TOKEN.into(), 15))
+}
+
+/// A token holding the revenue of the ticket app.
+fn token() -> SimpleToken {
+    let metadata = TokenMetadata {
+        name: "Simple Token".to_string(),
+        symbol: "SMP".to_string(),
+        decimals: 0,
+    };
+    let mut token = SimpleToken::new(
+        1000,
+        "faucet.simple_token".to_string(),
+        "faucet.simple_token".to_string(),
+        None,
+        metadata,
+        "faucet.simple_token".to_string(),
+    );
+    token
+        .transfer("faucet.simple_token", TICKET_APP, 300)
+        .unwrap();
+    token
+}
+
+fn input(state: Vec<u8>, blobs: &[Blob], index: usize) -> ContractInput {
+    ContractInput {
+        state,
+        identity: Identity(ATTACKER.to_string()),
+        index: BlobIndex(index),
+        blobs: blobs.to_vec(),
+        tx_hash: TxHash("01".to_string()),
+        tx_ctx: None,
+        private_input: vec![],
+    }
+}
+
+/// Blobs of a transaction where the ticket app blob at index 2 is the caller of a transfer,
+/// debiting the account of the ticket app.
+fn steal(ticket_app_action: TicketAppAction) -> Vec<Blob> {
+    let steal = SimpleTokenAction::Transfer {
+        recipient: ATTACKER.to_string(),
+        amount: 300,
+    };
+    vec![
+        Blob {
+            contract_name: "id".into(),
+            data: BlobData(vec![]),
+        },
+        SimpleTokenAction::Transfer {
+            recipient: TICKET_APP.to_string(),
+            amount: 15,
+        }
+        .as_blob(TOKEN.into(), None, None),
+        ticket_app_action.as_blob(TICKET_APP.into(), None, Some(vec![BlobIndex(3)])),
+        steal.as_blob(TOKEN.into(), Some(BlobIndex(2)), None),
+    ]
+}
+
+#[test]
+fn ticket_app_blob_cannot_call_a_transfer() {
+    for action in [TicketAppAction::HasTicket {}, TicketAppAction::BuyTicket {}] {
+        let blobs = steal(action);
+
+        // On its own, the token sees the ticket app as the caller and debits its account
+        let mut token = token();
+        token
+            .execute(&input(token.as_bytes().unwrap(), &blobs, 3))
+            .unwrap();
+        assert_eq!(token.balance_of(TICKET_APP).unwrap(), 0);
+
+        // So the ticket app blob must fail, for the whole transaction to fail
+        let mut ticket_app = ticket_app();
+        let err = ticket_app
+            .execute(&input(ticket_app.as_bytes().unwrap(), &blobs, 2))
+            .unwrap_err();
+        assert_eq!(err, "Ticket app blob must not have callees");
+    }
+}
+
+#[test]
+fn ticket_app_blob_cannot_have_a_caller() {
+    // The transfer blob lists the ticket app blob as its callee, so that the sdk accepts the caller
+    let blobs = vec![
+        Blob {
+            contract_name: "id".into(),
+            data: BlobData(vec![]),
+        },
+        SimpleTokenAction::Transfer {
+            recipient: TICKET_APP.to_string(),
+            amount: 15,
+        }
+        .as_blob(TOKEN.into(), None, Some(vec![BlobIndex(2)])),
+        TicketAppAction::BuyTicket {}.as_blob(TICKET_APP.into(), Some(BlobIndex(1)), None),
+    ];
+
+    let mut ticket_app = TicketAppState::new(vec![], (TOKEN.into(), 15));
+    let err = ticket_app
+        .execute(&input(ticket_app.as_bytes().unwrap(), &blobs, 2))
+        .unwrap_err();
+    assert_eq!(err, "Ticket app blob must not have a caller");
+}
@@ -1,13 +1,20 @@
 use clap::{Parser, Subcommand};
 
 use client_sdk::helpers::risc0::Risc0Prover;
+use client_sdk::rest_client::NodeApiHttpClient;
 use contract::SimpleToken;
 use contract::SimpleTokenAction;
+use contract::SimpleTokenOutput;
+use contract_identity::keys::SecretKey;
+use contract_identity::Authorization;
 use contract_identity::IdentityAction;
+use contract_identity::IdentityOutput;
 use contract_ticket_app::TicketAppAction;
+use contract_ticket_app::TicketAppOutput;
 use contract_ticket_app::TicketAppState;
 use sdk::api::APIRegisterContract;
 use sdk::BlobTransaction;
+use sdk::ContractAction;
 use sdk::Identity;
 use sdk::ProofTransaction;
 use sdk::{ContractInput, ContractName, HyleContract};
@@ -38,6 +45,11 @@ struct Cli {
     #[arg(long, default_value = "pass")]
     pub pass: String,
 
+    /// Session key of the user, as `<scheme>:<hex>`, used instead of the password.
+    /// It must be scoped to the ticket app and its token.
+    #[arg(long)]
+    pub session: Option<SecretKey>,
+
     #[arg(long, default_value = "0")]
     pub nonce: String,
 }
@@ -58,7 +70,7 @@ async fn main() {
 
     let cli = Cli::parse();
 
-    let client = client_sdk::rest_client::NodeApiHttpClient::new(cli.host).unwrap();
+    let client = NodeApiHttpClient::new(cli.host).unwrap();
 
     let contract_name = &cli.contract_name.clone();
 
@@ -102,9 +114,17 @@ async fn main() {
 
             let identity = Identity(cli.user.clone());
 
-            let identity_cf: IdentityAction = IdentityAction::VerifyIdentity {
-                account: identity.0.clone(),
-                nonce: cli.nonce.parse().unwrap(),
+            let nonce = cli.nonce.parse().unwrap();
+            let identity_cf: IdentityAction = match &cli.session {
+                Some(session) => IdentityAction::VerifySession {
+                    account: identity.0.clone(),
+                    nonce,
+                    key: session.public_key(),
+                },
+                None => IdentityAction::VerifyIdentity {
+                    account: identity.0.clone(),
+                    nonce,
+                },
             };
 
             let identity_contract_name = cli.user.rsplit_once(".").unwrap().1.to_string();
@@ -116,24 +136,13 @@ async fn main() {
                         borsh::to_vec(&identity_cf).expect("Failed to encode Identity action"),
                     ),
                 },
-                // Init pair 0 amount
-                sdk::Blob {
-                    contract_name: initial_state.ticket_price.0.clone(),
-                    data: sdk::BlobData(
-                        borsh::to_vec(&SimpleTokenAction::Transfer {
-                            recipient: contract_name.clone(),
-                            amount: initial_state.ticket_price.1,
-                        })
-                        .expect("Failed to encode Erc20 transfer action"),
-                    ),
-                },
-                sdk::Blob {
-                    contract_name: contract_name.clone().into(),
-                    data: sdk::BlobData(
-                        borsh::to_vec(&TicketAppAction::BuyTicket {})
-                            .expect("Failed to encode Buy Ticket action"),
-                    ),
-                },
+                // The ticket is paid by the buyer, so the transfer has no caller
+                SimpleTokenAction::Transfer {
+                    recipient: contract_name.clone(),
+                    amount: initial_state.ticket_price.1,
+                }
+                .as_blob(initial_state.ticket_price.0.clone(), None, None),
+                TicketAppAction::BuyTicket {}.as_blob(contract_name.clone().into(), None, None),
             ];
 
             println!("Blobs {:?}", blobs.clone());
@@ -144,6 +153,9 @@ async fn main() {
             let blob_tx_hash = client.send_tx_blob(&blob_tx).await.unwrap();
             println!("✅ Blob tx sent. Tx hash: {}", blob_tx_hash);
 
+            // Vesting and sessions depend on the height of the block including the transaction
+            let tx_ctx = fetch_tx_ctx(&client, &blob_tx_hash).await;
+
             // prove tx
 
             println!("Running and proving TicketApp blob");
@@ -154,11 +166,15 @@ async fn main() {
                 identity: identity.clone(),
                 tx_hash: blob_tx_hash.clone().into(),
                 private_input: vec![],
-                tx_ctx: None,
+                tx_ctx: Some(tx_ctx.clone()),
                 blobs: blobs.clone(),
                 index: sdk::BlobIndex(2),
             };
 
+            let res = initial_state.clone().execute(&inputs).unwrap();
+            let output = TicketAppOutput::decode(&res.0).expect("failed to decode program output");
+            println!("🚀 Executed: {}", output.event());
+
             // Generate the zk proof
             //
             let proof = ticket_prover.prove(inputs).await.unwrap();
@@ -187,11 +203,18 @@ async fn main() {
                 identity: identity.clone(),
                 tx_hash: blob_tx_hash.clone().into(),
                 private_input: vec![],
-                tx_ctx: None,
+                tx_ctx: Some(tx_ctx.clone()),
                 blobs: blobs.clone(),
                 index: sdk::BlobIndex(1),
             };
 
+            let res = initial_state_a.clone().execute(&inputs).unwrap();
+            let output =
+                SimpleTokenOutput::decode(&res.0).expect("failed to decode program output");
+            for event in output.events() {
+                println!("🚀 Executed: {}", event);
+            }
+
             // Generate the zk proof
             let proof = token_prover.prove(inputs).await.unwrap();
 
@@ -214,17 +237,36 @@ async fn main() {
                 .state
                 .into();
 
+            // A session signs the transaction and its blobs, instead of sending the password
+            let private_input = match &cli.session {
+                Some(session) => {
+                    let authorization = Authorization::new(
+                        &identity.0,
+                        nonce,
+                        &blob_tx_hash,
+                        &blobs,
+                        sdk::BlobIndex(0),
+                    );
+                    session.sign(&authorization.message())
+                }
+                None => cli.pass.into_bytes().to_vec(),
+            };
+
             // Build the contract input
             let inputs = ContractInput {
                 state: initial_state_id.as_bytes().unwrap(),
                 identity: identity.clone(),
                 tx_hash: blob_tx_hash.clone().into(),
-                private_input: cli.pass.into_bytes().to_vec(),
-                tx_ctx: None,
+                private_input,
+                tx_ctx: Some(tx_ctx),
                 blobs: blobs.clone(),
                 index: sdk::BlobIndex(0),
             };
 
+            let res = initial_state_id.clone().execute(&inputs).unwrap();
+            let output = IdentityOutput::decode(&res.0).expect("failed to decode program output");
+            println!("🚀 Executed: {}", output.event());
+
             // Generate the zk proof
             let proof = identity_prover.prove(inputs).await.unwrap();
 
@@ -256,3 +298,14 @@ async fn main() {
         }
     }
 }
+
+/// Waits for the blob transaction to be included in a block, and returns its context.
+async fn fetch_tx_ctx(client: &NodeApiHttpClient, tx_hash: &sdk::TxHash) -> sdk::TxContext {
+    for _ in 0..30 {
+        if let Ok(tx) = client.get_unsettled_tx(tx_hash).await {
+            return tx.tx_context;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    panic!("blob tx {} was not included in a block", tx_hash);
+}